@@ -0,0 +1,46 @@
+// `cargo bench` compares the exact `Ratio` path with the f64 path on long data.
+
+use asciigraph::{Downsampling, Graph};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+const LENGTH: usize = 100_000;
+
+fn sample_data() -> Vec<f64> {
+    (0..LENGTH).map(|i| (i as f64 * 0.001).sin() * 100.0 + (i % 7) as f64 * 0.37).collect()
+}
+
+fn render_1d(c: &mut Criterion) {
+    let data = sample_data();
+    let mut group = c.benchmark_group("render_1d_100k");
+    group.sample_size(10);
+
+    for downsampling in [Downsampling::MinMax, Downsampling::Mean, Downsampling::Lttb] {
+        group.bench_with_input(BenchmarkId::new("exact", format!("{downsampling:?}")), &downsampling, |b, downsampling| b.iter(
+            || Graph::default().set_1d_data(black_box(&data)).set_downsampling(*downsampling).draw()
+        ));
+        group.bench_with_input(BenchmarkId::new("f64", format!("{downsampling:?}")), &downsampling, |b, downsampling| b.iter(
+            || Graph::default().set_1d_data_f64(black_box(&data)).set_downsampling(*downsampling).draw()
+        ));
+    }
+
+    group.finish();
+}
+
+fn render_time_series(c: &mut Criterion) {
+    let data = sample_data().into_iter().enumerate().map(|(i, n)| (1_700_000_000 + i as i64 * 13, n)).collect::<Vec<_>>();
+    let mut group = c.benchmark_group("render_time_series_100k");
+    group.sample_size(10);
+
+    group.bench_function("exact", |b| b.iter(
+        || Graph::default().set_time_series_data(black_box(&data)).draw()
+    ));
+    group.bench_function("f64", |b| b.iter(
+        || Graph::default().set_time_series_data_f64(black_box(&data)).draw()
+    ));
+
+    group.finish();
+}
+
+criterion_group!(benches, render_1d, render_time_series);
+criterion_main!(benches);
@@ -0,0 +1,247 @@
+use crate::alignment::Alignment;
+use crate::charset::Glyphs;
+use crate::color::{Color, ColorMode};
+use crate::layout::Rect;
+use crate::lines::Lines;
+use hmath::Ratio;
+use std::str::FromStr;
+
+/// A mark on the plot at a data coordinate, like a deploy or an incident. See `Graph::add_annotation`.
+///
+/// The index is the index of the data, like `Graph::add_labeled_interval`. For time series, it's the index of the
+/// points, and the annotation is drawn at the column of its timestamp. For box plots, it's the index of the category.
+///
+/// ```nohighlight
+/// graph.add_annotation(Annotation::new(12, AnnotationStyle::VerticalLine).set_label("deploy"))
+///     .add_annotation(Annotation::new(30, AnnotationStyle::Callout).set_value(8.5).set_label("incident"));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Annotation {
+    pub(crate) index: usize,
+
+    // if it's not set, it's the value that's drawn at `index`
+    pub(crate) value: Option<Ratio>,
+
+    pub(crate) style: AnnotationStyle,
+    pub(crate) label: Option<String>,
+    pub(crate) color: Option<Color>,
+}
+
+impl Annotation {
+    pub fn new(index: usize, style: AnnotationStyle) -> Self {
+        Annotation {
+            index,
+            value: None,
+            style,
+            label: None,
+            color: None,
+        }
+    }
+
+    /// Where the marker (or the callout) points at. If it's not set, it's the value that's drawn at the index,
+    /// which is the top of the bar, or the median of a box plot. Vertical lines ignore it.
+    ///
+    /// `T` can be any number type, including f32 and f64. NaN is converted to 0, -Inf is converted to f32::MIN and Inf to f32::MAX (or f64).
+    pub fn set_value<T: TryInto<Ratio>>(&mut self, value: T) -> &mut Self {
+        self.value = Some(value.try_into().unwrap_or(Ratio::zero()));
+
+        self
+    }
+
+    /// The text next to the annotation. It can have multiple lines.
+    pub fn set_label<T: ToString>(&mut self, label: T) -> &mut Self {
+        self.label = Some(label.to_string());
+
+        self
+    }
+
+    /// The color of the marker, the line, and the label.
+    pub fn set_color(&mut self, color: Option<Color>) -> &mut Self {
+        self.color = color;
+
+        self
+    }
+}
+
+/// How an `Annotation` is drawn.
+///
+/// It's default to `AnnotationStyle::Marker`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AnnotationStyle {
+    /// The marker glyph of the charset at (index, value), and the label next to it.
+    #[default]
+    Marker,
+
+    /// A vertical line through the plot at the index, and the label at the top of the plot.
+    /// The line is drawn over the bar at the index.
+    VerticalLine,
+
+    /// The marker at (index, value), and the label in a box near it.
+    Callout,
+}
+
+impl FromStr for AnnotationStyle {
+    type Err = String;
+
+    /// returns Err(s) if it fails
+    fn from_str(s: &str) -> Result<AnnotationStyle, String> {
+        match s.replace(" ", "").replace("_", "").replace("-", "").to_ascii_lowercase() {
+            s if s == "marker" => Ok(AnnotationStyle::Marker),
+            s if s == "verticalline" => Ok(AnnotationStyle::VerticalLine),
+            s if s == "callout" => Ok(AnnotationStyle::Callout),
+            _ => Err(s.to_string()),
+        }
+    }
+}
+
+/// It draws `annotations` on `plot`. Each annotation comes with the cell that it points at, in the coordinates of `plot`.
+/// The cell must be in `area`, and vertical lines point at the top row of `area`.
+///
+/// The markers and the lines are drawn first, then the labels are placed one by one, at the first place that's in `area`
+/// and doesn't overlap the markers, the lines and the labels placed before. If there's no such place, the label is not drawn.
+///
+/// The markers and the lines overwrite the cells of the plot, and an annotation overwrites the markers and the lines
+/// of the annotations before it, if they're on the same cell. The labels never overwrite the markers and the lines.
+pub fn draw_annotations(plot: &mut Lines, annotations: &[(&Annotation, usize, usize)], area: &Rect, glyphs: &Glyphs) {
+    // the cells that the labels cannot cover
+    let mut occupied = Vec::with_capacity(annotations.len() * 2);
+
+    for (annotation, x, y) in annotations.iter() {
+        match annotation.style {
+            AnnotationStyle::VerticalLine => {
+                for y in area.y..(area.y + area.height) {
+                    plot.set(*x, y, glyphs.vertical);
+                    plot.set_color(*x, y, annotation.color.clone());
+                }
+
+                occupied.push(Rect { x: *x, y: area.y, width: 1, height: area.height });
+            },
+            AnnotationStyle::Marker
+            | AnnotationStyle::Callout => {
+                plot.set(*x, *y, glyphs.marker);
+                plot.set_color(*x, *y, annotation.color.clone());
+                occupied.push(Rect { x: *x, y: *y, width: 1, height: 1 });
+            },
+        }
+    }
+
+    for (annotation, x, y) in annotations.iter() {
+        let label = match &annotation.label {
+            Some(label) => label,
+            None => { continue; },
+        };
+
+        let mut label = Lines::from_string(label, Alignment::First, &ColorMode::None);
+
+        if annotation.style == AnnotationStyle::Callout {
+            label = label.add_border([true; 4], glyphs);
+        }
+
+        label.set_color_all(annotation.color.clone());
+
+        let (x, y) = (*x as i64, *y as i64);
+        let (w, h) = (label.get_width() as i64, label.get_height() as i64);
+        let (top, bottom) = (area.y as i64, (area.y + area.height) as i64);
+
+        // (columns, rows) of the top-left corner, in the order of preference
+        let (columns, rows): ([i64; 2], Vec<i64>) = match annotation.style {
+            // right after the marker with a space, or right before it
+            AnnotationStyle::Marker => (
+                [x + 2, x - w - 1],
+                (0..(bottom - top)).flat_map(|d| [y - d, y + d]).skip(1).collect(),
+            ),
+
+            // next to the line, from the top
+            AnnotationStyle::VerticalLine => (
+                [x + 1, x - w],
+                (top..bottom).collect(),
+            ),
+
+            // above the marker, then below it
+            AnnotationStyle::Callout => (
+                [x + 1, x - w],
+                (top..=(y - h)).rev().chain((y + 1)..bottom).collect(),
+            ),
+        };
+
+        let place = rows.iter().flat_map(
+            |row| columns.iter().map(move |column| (*column, *row))
+        ).filter(
+            |(column, row)| *column >= area.x as i64 && column + w <= (area.x + area.width) as i64
+                && *row >= top && row + h <= bottom
+        ).map(
+            |(column, row)| Rect { x: column as usize, y: row as usize, width: w as usize, height: h as usize }
+        ).find(
+            |rect| !occupied.iter().any(|occupied| overlaps(rect, occupied))
+        );
+
+        if let Some(rect) = place {
+            plot.blit_mut(&label, rect.x, rect.y, None);
+            occupied.push(rect);
+        }
+    }
+}
+
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Annotation, AnnotationStyle, Graph};
+
+    fn draw(annotations: &[Annotation]) -> Vec<Vec<char>> {
+        let mut graph = Graph::new(10, 6);
+        graph.set_1d_data(&[1, 2, 3, 4, 5, 5, 4, 3, 2, 1]);
+
+        for annotation in annotations.iter() {
+            graph.add_annotation(annotation);
+        }
+
+        graph.draw().lines().map(|line| line.chars().collect()).collect()
+    }
+
+    fn marker(index: usize, label: &str) -> Annotation {
+        let mut annotation = Annotation::new(index, AnnotationStyle::Marker);
+        annotation.set_label(label);
+
+        annotation
+    }
+
+    fn count(output: &[Vec<char>], c: char) -> usize {
+        output.iter().map(|line| line.iter().filter(|c_| **c_ == c).count()).sum()
+    }
+
+    #[test]
+    fn adjacent_markers() {
+        let output = draw(&[
+            marker(4, "a"),
+            marker(5, "b"),
+        ]);
+
+        // both markers are drawn at the same row, next to each other
+        let row = output.iter().find(|line| line.contains(&'◆')).unwrap();
+        let column = row.iter().position(|c| *c == '◆').unwrap();
+        assert_eq!(row[column + 1], '◆');
+        assert_eq!(count(&output, '◆'), 2);
+
+        // and the labels don't cover them
+        assert_eq!(count(&output, 'a'), 1);
+        assert_eq!(count(&output, 'b'), 1);
+    }
+
+    #[test]
+    fn later_annotation_wins() {
+        let line_then_marker = draw(&[
+            Annotation::new(4, AnnotationStyle::VerticalLine),
+            Annotation::new(4, AnnotationStyle::Marker),
+        ]);
+        let marker_then_line = draw(&[
+            Annotation::new(4, AnnotationStyle::Marker),
+            Annotation::new(4, AnnotationStyle::VerticalLine),
+        ]);
+
+        assert_eq!(count(&line_then_marker, '◆'), 1);
+        assert_eq!(count(&marker_then_line, '◆'), 0);
+    }
+}
@@ -0,0 +1,292 @@
+use crate::alignment::Alignment;
+use crate::charset::Charset;
+use crate::color::{Color, ColorMode};
+use crate::layout::Rect;
+use crate::lines::{Grapheme, Lines};
+use std::str::FromStr;
+
+/// A rectangular surface of characters and their colors. You can draw your own widgets on it,
+/// or annotations on top of a graph (see `Graph::draw_canvas` and `Graph::render_into`).
+///
+/// (0, 0) is the top-left corner. Anything that's drawn outside the canvas is cropped.
+/// Each drawing method colors the cells it draws with `color`, and `None` removes the colors of the cells.
+///
+/// ```nohighlight
+/// let mut canvas = graph.draw_canvas();
+/// let area = graph.layout().plot_area;
+/// canvas.draw_box(area, Some(Color::Red))
+///     .draw_text(area.x + 1, area.y, "peak", None);
+/// println!("{canvas}");
+/// ```
+#[derive(Clone)]
+pub struct Canvas {
+    lines: Lines,
+    color_mode: ColorMode,
+    charset: Charset,
+}
+
+impl Canvas {
+    /// A canvas filled with spaces.
+    pub fn new(width: usize, height: usize) -> Self {
+        Canvas::from_lines(Lines::new(width, height), ColorMode::None, Charset::Unicode)
+    }
+
+    pub(crate) fn from_lines(lines: Lines, color_mode: ColorMode, charset: Charset) -> Self {
+        Canvas { lines, color_mode, charset }
+    }
+
+    pub(crate) fn get_lines(&self) -> &Lines {
+        &self.lines
+    }
+
+    #[cfg(feature = "ratatui")]
+    pub(crate) fn get_color_mode(&self) -> &ColorMode {
+        &self.color_mode
+    }
+
+    pub(crate) fn get_lines_mut(&mut self) -> &mut Lines {
+        &mut self.lines
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.lines.get_width()
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.lines.get_height()
+    }
+
+    /// `None` if (x, y) is outside the canvas.
+    pub fn get(&self, x: usize, y: usize) -> Option<&Grapheme> {
+        if self.contains(x, y) {
+            Some(self.lines.get(x, y))
+        }
+
+        else {
+            None
+        }
+    }
+
+    /// `None` if (x, y) is outside the canvas or the cell is not colored.
+    pub fn get_color(&self, x: usize, y: usize) -> Option<Color> {
+        if self.contains(x, y) {
+            self.lines.get_color(x, y)
+        }
+
+        else {
+            None
+        }
+    }
+
+    pub fn set_color_mode(&mut self, color_mode: ColorMode) -> &mut Self {
+        self.color_mode = color_mode;
+
+        self
+    }
+
+    /// It's used by `draw_box` and quadrant lines. It's default to `Charset::Unicode`.
+    pub fn set_charset(&mut self, charset: Charset) -> &mut Self {
+        self.charset = charset;
+
+        self
+    }
+
+    /// If `c` is a double-width character, it also occupies `(x + 1, y)`.
+    pub fn set_char(&mut self, x: usize, y: usize, c: char, color: Option<Color>) -> &mut Self {
+        if self.contains(x, y) {
+            self.lines.set(x, y, c);
+            self.lines.set_color(x, y, color);
+        }
+
+        self
+    }
+
+    /// It colors a cell without changing its character.
+    pub fn set_color(&mut self, x: usize, y: usize, color: Option<Color>) -> &mut Self {
+        if self.contains(x, y) {
+            self.lines.set_color(x, y, color);
+        }
+
+        self
+    }
+
+    /// `text` starts at (x, y). If it has multiple lines, the next line starts at (x, y + 1).
+    /// Double-width characters take 2 cells, and the spaces in `text` are drawn, too.
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str, color: Option<Color>) -> &mut Self {
+        let mut text = Lines::from_string(text, Alignment::First, &ColorMode::None);
+        text.set_color_all(color);
+        self.lines.blit_mut(&text, x, y, None);
+
+        self
+    }
+
+    /// A horizontal line of `c`, from (x, y) to (x + length - 1, y).
+    pub fn draw_horizontal_line(&mut self, x: usize, y: usize, length: usize, c: char, color: Option<Color>) -> &mut Self {
+        self.fill_rect(Rect { x, y, width: length, height: 1 }, c, color)
+    }
+
+    /// A vertical line of `c`, from (x, y) to (x, y + length - 1).
+    pub fn draw_vertical_line(&mut self, x: usize, y: usize, length: usize, c: char, color: Option<Color>) -> &mut Self {
+        self.fill_rect(Rect { x, y, width: 1, height: length }, c, color)
+    }
+
+    /// It fills `rect` with `c`.
+    pub fn fill_rect(&mut self, rect: Rect, c: char, color: Option<Color>) -> &mut Self {
+        for y in rect.y..(rect.y + rect.height).min(self.get_height()) {
+            for x in rect.x..(rect.x + rect.width).min(self.get_width()) {
+                self.set_char(x, y, c, color.clone());
+            }
+        }
+
+        self
+    }
+
+    /// It draws the border of `rect` with the lines and the corners of the charset. The inside is not changed.
+    pub fn draw_box(&mut self, rect: Rect, color: Option<Color>) -> &mut Self {
+        if rect.width == 0 || rect.height == 0 {
+            return self;
+        }
+
+        let glyphs = self.charset.glyphs().clone();
+        let (right, bottom) = (rect.x + rect.width - 1, rect.y + rect.height - 1);
+
+        self.draw_horizontal_line(rect.x, rect.y, rect.width, glyphs.horizontal, color.clone())
+            .draw_horizontal_line(rect.x, bottom, rect.width, glyphs.horizontal, color.clone())
+            .draw_vertical_line(rect.x, rect.y, rect.height, glyphs.vertical, color.clone())
+            .draw_vertical_line(right, rect.y, rect.height, glyphs.vertical, color.clone())
+            .set_char(rect.x, rect.y, glyphs.corners[0], color.clone())
+            .set_char(right, rect.y, glyphs.corners[1], color.clone())
+            .set_char(rect.x, bottom, glyphs.corners[2], color.clone())
+            .set_char(right, bottom, glyphs.corners[3], color)
+    }
+
+    /// A straight line from `from` to `to` (Bresenham's line algorithm). Both ends are drawn.
+    ///
+    /// The coordinates are in dots of `resolution`, not in cells. For example, with `Resolution::Braille`,
+    /// (3, 5) is the dot at (1, 1) of the cell at (1, 1), because a cell has 2x4 dots.
+    /// If a cell already has quadrants (or braille dots), the new dots are added to them.
+    pub fn draw_line(&mut self, from: (usize, usize), to: (usize, usize), resolution: Resolution, color: Option<Color>) -> &mut Self {
+        let (x0, y0) = (from.0 as i64, from.1 as i64);
+        let (x1, y1) = (to.0 as i64, to.1 as i64);
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+        let (mut x, mut y) = (x0, y0);
+        let mut error = dx + dy;
+
+        loop {
+            self.draw_dot(x as usize, y as usize, resolution, color.clone());
+
+            if x == x1 && y == y1 {
+                break;
+            }
+
+            let e2 = error * 2;
+
+            if e2 >= dy {
+                error += dy;
+                x += sx;
+            }
+
+            if e2 <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+
+        self
+    }
+
+    /// A dot at (x, y), in dots of `resolution`. See `draw_line`.
+    pub fn draw_dot(&mut self, x: usize, y: usize, resolution: Resolution, color: Option<Color>) -> &mut Self {
+        match resolution {
+            Resolution::Cell(c) => self.set_char(x, y, c, color),
+            Resolution::Quadrant => {
+                let (cell_x, cell_y) = (x / 2, y / 2);
+
+                if !self.contains(cell_x, cell_y) {
+                    return self;
+                }
+
+                let quadrants = self.charset.glyphs().quadrants;
+                let mask = match self.lines.get(cell_x, cell_y) {
+                    Grapheme::Char(c) => quadrants.iter().position(|q| q == c).unwrap_or(0),
+                    _ => 0,
+                } | (1 << ((1 - y % 2) * 2 + (1 - x % 2)));
+
+                self.set_char(cell_x, cell_y, quadrants[mask], color)
+            },
+            Resolution::Braille => {
+                let (cell_x, cell_y) = (x / 2, y / 4);
+
+                if !self.contains(cell_x, cell_y) {
+                    return self;
+                }
+
+                let dots = match self.lines.get(cell_x, cell_y) {
+                    Grapheme::Char(c @ '\u{2800}'..='\u{28ff}') => *c as u32 - 0x2800,
+                    _ => 0,
+                } | BRAILLE_DOTS[y % 4][x % 2];
+
+                self.set_char(cell_x, cell_y, char::from_u32(0x2800 + dots).unwrap(), color)
+            },
+        }
+    }
+
+    /// It draws `other` at (x, y). The cells of `other` that are `transparent_char` are not drawn,
+    /// so that you can draw something on top of a graph without erasing it, with `Some(' ')`.
+    pub fn blit(&mut self, other: &Canvas, x: usize, y: usize, transparent_char: Option<char>) -> &mut Self {
+        self.lines.blit_mut(&other.lines, x, y, transparent_char);
+
+        self
+    }
+
+    pub fn draw(&self) -> String {
+        self.lines.to_string(&self.color_mode)
+    }
+
+    fn contains(&self, x: usize, y: usize) -> bool {
+        x < self.get_width() && y < self.get_height()
+    }
+}
+
+// `BRAILLE_DOTS[y][x]` is the bit of the dot at (x, y) of a braille cell.
+const BRAILLE_DOTS: [[u32; 2]; 4] = [
+    [0x01, 0x08],
+    [0x02, 0x10],
+    [0x04, 0x20],
+    [0x40, 0x80],
+];
+
+use std::fmt;
+
+impl fmt::Display for Canvas {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.draw())
+    }
+}
+
+/// The resolution of `Canvas::draw_line` and `Canvas::draw_dot`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Resolution {
+    /// A dot is a cell, drawn with the character.
+    Cell(char),
+
+    /// A cell has 2x2 dots. It uses the quadrants of the charset of the canvas.
+    Quadrant,
+
+    /// A cell has 2x4 dots. Braille patterns are always unicode, regardless of the charset.
+    Braille,
+}
+
+impl FromStr for Resolution {
+    type Err = String;
+
+    /// returns Err(s) if it fails. `Resolution::Cell` cannot be parsed.
+    fn from_str(s: &str) -> Result<Resolution, String> {
+        match s.replace(" ", "").replace("_", "").replace("-", "").to_ascii_lowercase() {
+            s if s == "quadrant" => Ok(Resolution::Quadrant),
+            s if s == "braille" => Ok(Resolution::Braille),
+            _ => Err(s.to_string()),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::str::FromStr;
+
+/// Characters that the engine uses to draw graphs.
+///
+/// If you want to use your own glyphs, start from `Glyphs::unicode()` or `Glyphs::ascii()` and
+/// overwrite the fields you want to change.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Glyphs {
+    /// full, 3/4, 1/2 and 1/4 blocks of a bar
+    pub bars: [char; 4],
+
+    /// drawn at the top of a bar that's higher than `y_max`
+    pub overflow: char,
+
+    pub horizontal: char,
+    pub vertical: char,
+
+    /// top-left, top-right, bottom-left, bottom-right
+    pub corners: [char; 4],
+
+    /// left, right, top, bottom, cross (`├`, `┤`, `┬`, `┴`, `┼`)
+    pub tees: [char; 5],
+
+    /// start and end of a labeled interval
+    pub arrows: [char; 2],
+
+    /// the boundaries of a labeled interval in the plot area (see `IntervalHighlight::Boundaries`), and the vertical gridlines
+    pub dotted_vertical: char,
+
+    /// the horizontal gridlines, see `Graph::set_gridlines`
+    pub dotted_horizontal: char,
+
+    /// the delimiter line of a skipped range
+    pub skip_line: char,
+
+    /// the horizontal break is drawn by alternating these 2 characters
+    pub horizontal_break: [char; 2],
+
+    /// levels of a sparkline, from the lowest to the highest
+    pub sparkline: [char; 8],
+
+    /// cells of a heatmap, from the lowest to the highest
+    pub shades: [char; 4],
+
+    /// an outlier of a box plot
+    pub outlier: char,
+
+    /// the point of an annotation (see `Annotation`)
+    pub marker: char,
+
+    /// Glyphs of 2x2 dots, used by `set_2d_data_high_resolution` and big titles.
+    /// The index is a bit mask: top-left is 8, top-right is 4, bottom-left is 2 and bottom-right is 1.
+    pub quadrants: [char; 16],
+}
+
+impl Glyphs {
+    pub fn unicode() -> Self {
+        Glyphs {
+            bars: ['█', '▆', '▄', '▂'],
+            overflow: '^',
+            horizontal: '─',
+            vertical: '│',
+            corners: ['╭', '╮', '╰', '╯'],
+            tees: ['├', '┤', '┬', '┴', '┼'],
+            arrows: ['<', '>'],
+            dotted_vertical: '┊',
+            dotted_horizontal: '┈',
+            skip_line: '~',
+            horizontal_break: [')', '('],
+            sparkline: ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'],
+            shades: ['░', '▒', '▓', '█'],
+            outlier: 'o',
+            marker: '◆',
+            quadrants: [
+                ' ', '▗', '▖', '▄',
+                '▝', '▐', '▞', '▟',
+                '▘', '▚', '▌', '▙',
+                '▀', '▜', '▛', '█',
+            ],
+        }
+    }
+
+    pub fn ascii() -> Self {
+        Glyphs {
+            bars: ['#', '#', '=', '_'],
+            overflow: '^',
+            horizontal: '-',
+            vertical: '|',
+            corners: ['+'; 4],
+            tees: ['+'; 5],
+            arrows: ['<', '>'],
+            dotted_vertical: ':',
+            dotted_horizontal: '.',
+            skip_line: '~',
+            horizontal_break: [')', '('],
+            sparkline: ['_', '.', ',', '-', '=', '+', '*', '#'],
+            shades: ['.', ':', '*', '#'],
+            outlier: 'o',
+            marker: '*',
+            quadrants: [
+                ' ', '.', ',', '_',
+                '\'', ']', '/', 'J',
+                '`', '\\', '[', 'L',
+                '"', '7', 'P', '#',
+            ],
+        }
+    }
+
+    // `set_2d_data_high_resolution` and `asciibox` always use unicode quadrants.
+    // this function converts them to the glyphs of this table.
+    pub(crate) fn translate_quadrant(&self, c: char) -> char {
+        match UNICODE_GLYPHS.quadrants.iter().position(|q| *q == c) {
+            Some(index) => self.quadrants[index],
+            None => c,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Charset {
+    #[default]
+    Unicode,
+
+    /// For terminals and logs that cannot render unicode box-drawing characters.
+    Ascii,
+    Custom(Box<Glyphs>),
+}
+
+impl Charset {
+    pub fn glyphs(&self) -> &Glyphs {
+        match self {
+            Charset::Unicode => &UNICODE_GLYPHS,
+            Charset::Ascii => &ASCII_GLYPHS,
+            Charset::Custom(glyphs) => glyphs,
+        }
+    }
+}
+
+impl FromStr for Charset {
+    type Err = String;
+
+    /// returns Err(s) if it fails. `Charset::Custom` cannot be parsed.
+    fn from_str(s: &str) -> Result<Charset, String> {
+        match s.replace(" ", "").replace("_", "").replace("-", "").to_ascii_lowercase() {
+            s if s == "unicode" => Ok(Charset::Unicode),
+            s if s == "ascii" => Ok(Charset::Ascii),
+            _ => Err(s.to_string()),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    pub static ref UNICODE_GLYPHS: Glyphs = Glyphs::unicode();
+    pub static ref ASCII_GLYPHS: Glyphs = Glyphs::ascii();
+}
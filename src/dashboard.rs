@@ -0,0 +1,300 @@
+use crate::alignment::Alignment;
+use crate::canvas::Canvas;
+use crate::charset::Charset;
+use crate::color::{Color, ColorMode};
+use crate::graph::{Graph, SharedAxes, draw_title, share_axes};
+use crate::lines::Lines;
+use crate::live_graph::LiveGraph;
+use crate::proportion::ProportionChart;
+use crate::sparkline::Sparkline;
+
+/// Anything that can be placed in a `Dashboard`.
+#[derive(Clone)]
+pub enum Panel {
+    Graph(Box<Graph>),
+    Sparkline(Sparkline),
+    Proportion(ProportionChart),
+    Canvas(Canvas),
+}
+
+impl Panel {
+    fn draw_lines(&self) -> Lines {
+        match self {
+            Panel::Graph(graph) => graph.draw_lines(),
+            Panel::Sparkline(sparkline) => sparkline.draw_lines(),
+            Panel::Proportion(chart) => chart.draw_lines(),
+            Panel::Canvas(canvas) => canvas.get_lines().clone(),
+        }
+    }
+}
+
+impl From<&Graph> for Panel {
+    fn from(graph: &Graph) -> Self {
+        Panel::Graph(Box::new(graph.clone()))
+    }
+}
+
+impl From<&LiveGraph> for Panel {
+    fn from(live_graph: &LiveGraph) -> Self {
+        Panel::Graph(Box::new(live_graph.to_graph()))
+    }
+}
+
+impl From<&Sparkline> for Panel {
+    fn from(sparkline: &Sparkline) -> Self {
+        Panel::Sparkline(sparkline.clone())
+    }
+}
+
+impl From<&ProportionChart> for Panel {
+    fn from(chart: &ProportionChart) -> Self {
+        Panel::Proportion(chart.clone())
+    }
+}
+
+impl From<&Canvas> for Panel {
+    fn from(canvas: &Canvas) -> Self {
+        Panel::Canvas(canvas.clone())
+    }
+}
+
+/// It arranges graphs in rows and columns. Unlike `merge_horiz` and `merge_vert`, it takes graphs, not strings,
+/// so the graphs are rendered only once, with the `ColorMode` of the dashboard. The `ColorMode`s of the graphs are ignored.
+///
+/// The panels in the same column have the same width, so that the columns are aligned.
+///
+/// ```nohighlight
+/// let mut dashboard = Dashboard::default();
+/// dashboard.add_panel(0, &graph1)
+///     .add_panel(0, &graph2)
+///     .add_panel(1, &sparkline);
+/// println!("{dashboard}");
+/// ```
+#[derive(Clone)]
+pub struct Dashboard {
+    rows: Vec<Vec<Panel>>,
+    row_titles: Vec<Option<String>>,
+
+    title: Option<String>,
+    title_color: Option<Color>,
+
+    column_gap: usize,
+    row_gap: usize,
+
+    // it draws a border around each panel
+    borders: bool,
+
+    // how the panels of different heights in a row are aligned
+    alignment: Alignment,
+
+    // if it's set, the graphs in the same row share their axes
+    shared_axes: Option<SharedAxes>,
+
+    paddings: [usize; 4],
+
+    color_mode: ColorMode,
+    charset: Charset,
+}
+
+impl Dashboard {
+    /// It appends a panel to the `row`th row. If there's no such row, it adds empty rows.
+    pub fn add_panel<T: Into<Panel>>(&mut self, row: usize, panel: T) -> &mut Self {
+        while self.rows.len() <= row {
+            self.rows.push(vec![]);
+            self.row_titles.push(None);
+        }
+
+        self.rows[row].push(panel.into());
+
+        self
+    }
+
+    /// The title of the whole dashboard.
+    pub fn set_title<T: ToString>(&mut self, title: T) -> &mut Self {
+        self.title = Some(title.to_string());
+
+        self
+    }
+
+    pub fn set_title_color(&mut self, title_color: Option<Color>) -> &mut Self {
+        self.title_color = title_color;
+
+        self
+    }
+
+    /// A title shared by the panels of the `row`th row. If there's no such row, it adds empty rows.
+    pub fn set_row_title<T: ToString>(&mut self, row: usize, title: T) -> &mut Self {
+        while self.rows.len() <= row {
+            self.rows.push(vec![]);
+            self.row_titles.push(None);
+        }
+
+        self.row_titles[row] = Some(title.to_string());
+
+        self
+    }
+
+    pub fn set_column_gap(&mut self, column_gap: usize) -> &mut Self {
+        self.column_gap = column_gap;
+
+        self
+    }
+
+    pub fn set_row_gap(&mut self, row_gap: usize) -> &mut Self {
+        self.row_gap = row_gap;
+
+        self
+    }
+
+    /// It draws a border around each panel.
+    pub fn set_borders(&mut self, borders: bool) -> &mut Self {
+        self.borders = borders;
+
+        self
+    }
+
+    /// How the panels of different heights in a row are aligned. It's default to `Alignment::First` (top).
+    ///
+    /// It panics if `alignment` is `Alignment::Uniform`, which is not supported.
+    pub fn set_alignment(&mut self, alignment: Alignment) -> &mut Self {
+        assert!(alignment != Alignment::Uniform, "`Alignment::Uniform` is not supported by `Dashboard`");
+
+        self.alignment = alignment;
+
+        self
+    }
+
+    /// If it's set, the graphs in the same row share their axes. See `share_axes`.
+    pub fn set_shared_axes(&mut self, shared_axes: Option<SharedAxes>) -> &mut Self {
+        self.shared_axes = shared_axes;
+
+        self
+    }
+
+    /// top, bottom, left, right
+    pub fn set_paddings(&mut self, paddings: [usize; 4]) -> &mut Self {
+        self.paddings = paddings;
+
+        self
+    }
+
+    pub fn set_color_mode(&mut self, color_mode: ColorMode) -> &mut Self {
+        self.color_mode = color_mode;
+
+        self
+    }
+
+    /// It's used for the borders. The panels use their own charsets.
+    pub fn set_charset(&mut self, charset: Charset) -> &mut Self {
+        self.charset = charset;
+
+        self
+    }
+
+    /// It panics if any of the panels is not well-configured.
+    pub fn draw(&self) -> String {
+        self.draw_lines().to_string(&self.color_mode)
+    }
+
+    pub(crate) fn draw_lines(&self) -> Lines {
+        let glyphs = self.charset.glyphs();
+        let mut rows = self.rows.clone();
+
+        if let Some(shared_axes) = self.shared_axes {
+            for row in rows.iter_mut() {
+                let mut graphs = row.iter_mut().filter_map(
+                    |panel| match panel {
+                        Panel::Graph(graph) => Some(graph.as_mut()),
+                        _ => None,
+                    }
+                ).collect::<Vec<_>>();
+
+                share_axes(&mut graphs, shared_axes);
+            }
+        }
+
+        let panels = rows.iter().map(
+            |row| row.iter().map(
+                |panel| {
+                    let lines = panel.draw_lines();
+
+                    if self.borders {
+                        lines.add_border([true; 4], glyphs)
+                    } else {
+                        lines
+                    }
+                }
+            ).collect::<Vec<_>>()
+        ).collect::<Vec<_>>();
+
+        let mut column_widths = vec![];
+
+        for row in panels.iter() {
+            for (column, panel) in row.iter().enumerate() {
+                if column_widths.len() <= column {
+                    column_widths.push(0);
+                }
+
+                column_widths[column] = column_widths[column].max(panel.get_width());
+            }
+        }
+
+        let mut result = Lines::empty();
+
+        for (index, row) in panels.iter().enumerate() {
+            let mut row_lines = Lines::empty();
+
+            for (column, panel) in row.iter().enumerate() {
+                let gap = if column + 1 < row.len() { self.column_gap } else { 0 };
+                let panel = panel.add_padding([0, 0, 0, column_widths[column] - panel.get_width() + gap]);
+
+                row_lines = row_lines.merge_horizontally(&panel, self.alignment);
+            }
+
+            if let Some(title) = &self.row_titles[index] {
+                let title = Lines::from_string(title, Alignment::Center, &ColorMode::None);
+                row_lines = title.merge_vertically(&row_lines, Alignment::Center);
+            }
+
+            if index > 0 {
+                result = result.add_padding([0, self.row_gap, 0, 0]);
+            }
+
+            result = result.merge_vertically(&row_lines, Alignment::First);
+        }
+
+        if let Some(title) = &self.title {
+            let title = draw_title(title, false, self.title_color.clone(), glyphs);
+            result = title.merge_vertically(&result, Alignment::Center);
+        }
+
+        result.add_padding(self.paddings)
+    }
+}
+
+impl Default for Dashboard {
+    fn default() -> Self {
+        Dashboard {
+            rows: vec![],
+            row_titles: vec![],
+            title: None,
+            title_color: None,
+            column_gap: 2,
+            row_gap: 1,
+            borders: false,
+            alignment: Alignment::First,
+            shared_axes: None,
+            paddings: [0; 4],
+            color_mode: ColorMode::None,
+            charset: Charset::Unicode,
+        }
+    }
+}
+
+use std::fmt;
+
+impl fmt::Display for Dashboard {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.draw())
+    }
+}
@@ -0,0 +1,241 @@
+use crate::numeric::Number;
+use std::str::FromStr;
+
+/// When the data is longer than the width of the plot, the engine reduces the data so that each column has a value.
+///
+/// It's default to `Downsampling::MinMax`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Downsampling {
+    /// It keeps the minimum and the maximum of every 2 columns, so that spikes are not lost.
+    /// It's applied only when the data is longer than twice the width.
+    #[default]
+    MinMax,
+
+    /// mean of each column
+    Mean,
+
+    /// median of each column
+    Median,
+
+    /// the last value of each column
+    Last,
+
+    /// sum of each column
+    Sum,
+
+    /// Largest-Triangle-Three-Buckets: <https://skemman.is/bitstream/1946/15343/3/SS_MSthesis.pdf>
+    ///
+    /// It picks a point from each column which preserves the visual shape of the data.
+    Lttb,
+}
+
+impl Downsampling {
+    /// It returns `None` if `data` is short enough. Otherwise, the result has exactly `width` values.
+    pub(crate) fn downsample<N: Number>(&self, data: &[(String, N)], width: usize) -> Option<Vec<(String, N)>> {
+        if width == 0 {
+            return None;
+        }
+
+        match self {
+            Downsampling::MinMax if data.len() > width * 2 => Some(pick_min_max(data, width)),
+            Downsampling::MinMax => None,
+            _ if data.len() <= width => None,
+            Downsampling::Lttb => Some(lttb(data, width)),
+            _ => Some((0..width).map(
+                |column| {
+                    let bucket = &data[(column * data.len() / width)..((column + 1) * data.len() / width)];
+
+                    (bucket[0].0.clone(), self.aggregate(bucket))
+                }
+            ).collect()),
+        }
+    }
+
+    // `bucket` is never empty
+    fn aggregate<N: Number>(&self, bucket: &[(String, N)]) -> N {
+        match self {
+            Downsampling::Mean => sum(bucket).div_usize(bucket.len()),
+            Downsampling::Sum => sum(bucket),
+            Downsampling::Last => bucket[bucket.len() - 1].1.clone(),
+            Downsampling::Median => {
+                let mut values = bucket.iter().map(|(_, n)| n).collect::<Vec<_>>();
+                values.sort_unstable_by(|a, b| a.cmp_num(b));
+
+                if values.len() % 2 == 1 {
+                    values[values.len() / 2].clone()
+                }
+
+                else {
+                    values[values.len() / 2 - 1].add(values[values.len() / 2]).div_usize(2)
+                }
+            },
+            Downsampling::MinMax
+            | Downsampling::Lttb => unreachable!(),
+        }
+    }
+}
+
+impl FromStr for Downsampling {
+    type Err = String;
+
+    /// returns Err(s) if it fails
+    fn from_str(s: &str) -> Result<Downsampling, String> {
+        match s.replace(" ", "").replace("_", "").replace("-", "").to_ascii_lowercase() {
+            s if s == "minmax" => Ok(Downsampling::MinMax),
+            s if s == "mean" => Ok(Downsampling::Mean),
+            s if s == "median" => Ok(Downsampling::Median),
+            s if s == "last" => Ok(Downsampling::Last),
+            s if s == "sum" => Ok(Downsampling::Sum),
+            s if s == "lttb" => Ok(Downsampling::Lttb),
+            _ => Err(s.to_string()),
+        }
+    }
+}
+
+fn sum<N: Number>(bucket: &[(String, N)]) -> N {
+    let mut result = N::zero();
+
+    for (_, n) in bucket.iter() {
+        result.add_mut(n);
+    }
+
+    result
+}
+
+// Every 2 columns share a bucket, and the minimum and the maximum of the bucket are pushed in order.
+// If the width is odd, the last bucket has only 1 column, and only the maximum is pushed.
+fn pick_min_max<N: Number>(data: &[(String, N)], width: usize) -> Vec<(String, N)> {
+    let mut last_ind = 0;
+    let mut result = Vec::with_capacity(width);
+
+    for column in (0..width).step_by(2) {
+        let curr_ind = (column + 2).min(width) * data.len() / width;
+        let mut min_ind = 0;
+        let mut min_val = &data[last_ind].1;
+        let mut max_ind = 0;
+        let mut max_val = &data[last_ind].1;
+
+        for (ind, (_, val)) in data[last_ind..curr_ind].iter().enumerate() {
+            if val > max_val {
+                max_ind = ind;
+                max_val = val;
+            }
+
+            else if val < min_val {
+                min_ind = ind;
+                min_val = val;
+            }
+        }
+
+        if column + 1 == width {
+            result.push(data[last_ind + max_ind].clone());
+        }
+
+        else if min_ind < max_ind {
+            result.push(data[last_ind + min_ind].clone());
+            result.push(data[last_ind + max_ind].clone());
+        }
+
+        else {
+            result.push(data[last_ind + max_ind].clone());
+            result.push(data[last_ind + min_ind].clone());
+        }
+
+        last_ind = curr_ind;
+    }
+
+    result
+}
+
+// The first and the last points are always picked. The other points are divided into `width - 2` buckets,
+// and the point that makes the largest triangle with the previously picked point and the average of the next bucket is picked.
+fn lttb<N: Number>(data: &[(String, N)], width: usize) -> Vec<(String, N)> {
+    // there's no bucket between the first and the last points
+    match width {
+        1 => { return vec![data[0].clone()]; },
+        2 => { return vec![data[0].clone(), data[data.len() - 1].clone()]; },
+        _ => {},
+    }
+
+    let inner_len = data.len() - 2;
+    let buckets = width - 2;
+    let bucket_range = |bucket: usize| (1 + bucket * inner_len / buckets)..(1 + (bucket + 1) * inner_len / buckets);
+
+    let mut result = Vec::with_capacity(width);
+    let mut prev_ind = 0;
+    result.push(data[0].clone());
+
+    for bucket in 0..buckets {
+        let next_range = if bucket + 1 < buckets {
+            bucket_range(bucket + 1)
+        } else {
+            (data.len() - 1)..data.len()
+        };
+
+        let next_x = N::from_usize(next_range.start + next_range.end - 1).div_usize(2);
+        let next_y = sum(&data[next_range.clone()]).div_usize(next_range.len());
+
+        let prev_x = N::from_usize(prev_ind);
+        let prev_y = &data[prev_ind].1;
+
+        let mut max_area = None;
+        let mut max_ind = prev_ind;
+
+        for ind in bucket_range(bucket) {
+            // twice the area of the triangle
+            let area = prev_x.sub(&N::from_usize(ind)).mul(&next_y.sub(prev_y)).sub(
+                &prev_x.sub(&next_x).mul(&data[ind].1.sub(prev_y))
+            ).abs();
+
+            if max_area.as_ref().is_none_or(|max_area| &area > max_area) {
+                max_area = Some(area);
+                max_ind = ind;
+            }
+        }
+
+        result.push(data[max_ind].clone());
+        prev_ind = max_ind;
+    }
+
+    result.push(data[data.len() - 1].clone());
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hmath::Ratio;
+
+    #[test]
+    fn downsampled_length() {
+        let strategies = [
+            Downsampling::MinMax,
+            Downsampling::Mean,
+            Downsampling::Median,
+            Downsampling::Last,
+            Downsampling::Sum,
+            Downsampling::Lttb,
+        ];
+
+        for len in 1..40 {
+            let data = (0..len).map(|n| (n.to_string(), Ratio::from_i32(n * 7 % 5))).collect::<Vec<_>>();
+
+            for width in 1..8 {
+                for strategy in strategies.iter() {
+                    if let Some(result) = strategy.downsample(&data, width) {
+                        assert_eq!(result.len(), width, "{strategy:?}, len: {len}, width: {width}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lttb_small_widths() {
+        let data = (0..5).map(|n| (n.to_string(), Ratio::from_i32(n))).collect::<Vec<_>>();
+
+        assert_eq!(lttb(&data, 1), vec![data[0].clone()]);
+        assert_eq!(lttb(&data, 2), vec![data[0].clone(), data[4].clone()]);
+    }
+}
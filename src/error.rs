@@ -10,6 +10,12 @@ pub enum Error {
     },
     HmathError(hmath::ConversionError),
     UnknownKey(String),
+
+    /// a key that's not supported anymore, and the key to use instead
+    RemovedKey {
+        key: String,
+        replacement: String,
+    },
     JsonArrayLengthError {
         key: Option<String>,
         expected: usize,
@@ -17,6 +23,22 @@ pub enum Error {
     },
     InvalidColorName(String),
     InvalidColorMode(String),
+    InvalidCharset(String),
+    InvalidDownsampling(String),
+    InvalidOrientation(String),
+    InvalidHeightAllocation(String),
+    InvalidIntervalHighlight(String),
+    InvalidLegendPosition(String),
+    InvalidGridlines(String),
+
+    /// `[from, to]` of a range of `skip_range` whose `from` is greater than `to`, or that overlaps another range
+    InvalidSkipRange(String),
+
+    /// `min_gap_ratio` or `padding` of `skip_policy` that's not positive
+    InvalidSkipPolicy { key: String, value: String },
+
+    /// `skip_range` and `"orientation": "horizontal"` are set together, see `Graph::set_orientation`
+    UnsupportedSkipRange,
 }
 
 impl From<json::Error> for Error {
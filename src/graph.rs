@@ -1,22 +1,48 @@
 use crate::alignment::Alignment;
+use crate::annotation::{Annotation, AnnotationStyle, draw_annotations};
+use crate::canvas::Canvas;
+use crate::charset::{Charset, Glyphs};
 use crate::color::{Color, ColorMode};
+use crate::downsampling::Downsampling;
 use crate::format::NumberFormatter;
-use crate::interval::{Interval, draw_labeled_intervals};
-use crate::lines::Lines;
+use crate::interval::{Interval, IntervalHighlight, draw_labeled_intervals, highlight_labeled_intervals};
+use crate::gridlines::{Gridlines, draw_gridlines};
+use crate::layout::{Layout, Rect, YScale};
+use crate::legend::{Legend, LegendEntry, LegendPosition, draw_legend};
+use crate::lines::{Grapheme, Lines};
+use crate::numeric::{Number, Series};
+use crate::orientation::Orientation;
+use crate::skip_policy::{HeightAllocation, SkipPolicy};
 use crate::skip_value::SkipValue;
+use crate::time_series::{get_column, resample_time_series};
+use crate::utils::display_width;
 use hmath::Ratio;
-use std::collections::HashSet;
+use std::cell::OnceCell;
+use std::ops::Range;
 use std::sync::Arc;
 
+mod box_plot;
 mod merge;
+mod pick;
 mod setters;
+mod shared;
+
+#[cfg(feature = "ratatui")]
+mod widget;
+
+use box_plot::{BoxStats, plot_boxes_horizontal, plot_boxes_vertical};
 
 pub use merge::*;
+pub use shared::*;
 
 #[derive(Clone)]
 pub struct Graph {
     data: GraphData,
 
+    // (min, max) of 1-dimensional data. It's calculated when the data is set (or given by `LiveGraph`),
+    // so that the engine doesn't have to look at all the data again when it's not downsampled.
+    data_range: Option<(Ratio, Ratio)>,
+
     title: Option<String>,
     big_title: bool,
     title_color: Option<Color>,
@@ -26,6 +52,9 @@ pub struct Graph {
 
     block_width: Option<usize>,
 
+    // only box plots can be drawn horizontally
+    orientation: Orientation,
+
     x_label_margin: usize,
     y_label_margin: usize,
 
@@ -33,6 +62,13 @@ pub struct Graph {
     y_axis_label: Option<String>,
 
     labeled_intervals: Vec<Interval>,
+    interval_highlight: IntervalHighlight,
+    interval_highlight_color: Option<Color>,
+
+    annotations: Vec<Annotation>,
+    legend: Option<Legend>,
+    gridlines: Gridlines,
+    gridline_color: Option<Color>,
 
     y_min: Option<Ratio>,
     y_max: Option<Ratio>,
@@ -41,24 +77,73 @@ pub struct Graph {
 
     y_label_formatter: Arc<dyn NumberFormatter>,
 
+    // `y_label_width` is the minimum width of the y labels
+    show_y_labels: bool,
+    y_label_width: Option<usize>,
+
+    // (from, to) of the x axis of time series, in unix timestamps
+    time_range: Option<(i64, i64)>,
+
+    downsampling: Downsampling,
+
     // see comments in setters
     skip_value: SkipValue,
-    skip_skip_range: Option<(Option<Ratio>, Option<Ratio>)>,
+    skip_policy: SkipPolicy,
 
-    // TODO: Vec<(usize, usize)>?
-    horizontal_break: Option<(usize, usize)>,
+    // (start, end) of the hidden data, both inclusive
+    horizontal_breaks: Vec<(usize, usize)>,
 
     paddings: [usize; 4],
 
     color_mode: ColorMode,
     primary_color: Option<Color>,
+
+    // if it's set, the cells of a heatmap are colored instead of shaded
+    heatmap_colors: Option<Vec<Color>>,
+
+    charset: Charset,
+
+    // the data after it's downsampled, resampled or sorted. It's filled by the first drawing, and the setters
+    // of the data, the width and the ranges reset it.
+    cache: DataCache,
+}
+
+// Everything in it is derived from the data and the settings of `Graph`, so that the next drawings
+// (and `pick`, `get_skipped_ranges`...) don't have to calculate them again.
+#[derive(Clone, Default)]
+struct DataCache {
+    // downsampled 1-dimensional data. It's `None` if the data is not downsampled, so that the data is borrowed instead.
+    downsampled: OnceCell<Option<Vec<(String, Ratio)>>>,
+
+    // (resampled time series, x ticks)
+    #[allow(clippy::type_complexity)]
+    resampled: OnceCell<(Vec<(String, Ratio)>, Vec<(usize, String)>)>,
+
+    // (category, value) of all the values of the boxes, including the outliers
+    box_plot_values: OnceCell<Vec<(String, Ratio)>>,
+
+    // statistics of `get_y_axis_data`
+    stats: OnceCell<DataStats>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
 enum GraphData {
-    Data1D (Vec<(String, Ratio)>),
+    Data1D (Series<String>),
     Data2D {
-        data: Vec<(usize, usize, u16)>,
+        data: Vec<(usize, usize, char)>,
+        x_labels: Vec<Option<String>>,
+        y_labels: Vec<Option<String>>,
+    },
+
+    // (unix timestamp in seconds, value), sorted by timestamps
+    TimeSeries(Series<i64>),
+
+    // (category, statistics of its samples), `None` if the category has no samples
+    BoxPlot(Vec<(String, Option<BoxStats>)>),
+
+    // data[y][x]
+    Heatmap {
+        data: Vec<Vec<Ratio>>,
         x_labels: Vec<Option<String>>,
         y_labels: Vec<Option<String>>,
     },
@@ -66,17 +151,10 @@ enum GraphData {
 }
 
 impl GraphData {
-    pub fn unwrap_1d(&self) -> &Vec<(String, Ratio)> {
-        if let GraphData::Data1D(v) = self {
-            v
-        } else {
-            panic!("Unable to unwrap 1d data from {self:?}")
-        }
-    }
-
-    pub fn unwrap_2d(&self) -> ( &Vec<(usize, usize, u16)>, &Vec<Option<String>>, &Vec<Option<String>> ) {
+    #[allow(clippy::type_complexity)]
+    pub fn unwrap_2d(&self) -> ( &Vec<(usize, usize, char)>, &Vec<Option<String>>, &Vec<Option<String>> ) {
         if let GraphData::Data2D { data, x_labels, y_labels } = self {
-            (&data, &x_labels, &y_labels)
+            (data, x_labels, y_labels)
         } else {
             panic!("Unable to unwrap 2d data from {self:?}")
         }
@@ -86,6 +164,11 @@ impl GraphData {
         match self {
             GraphData::Data1D(data) => data.len(),
             GraphData::Data2D { data, .. } => data.len(),
+            GraphData::TimeSeries(data) => data.len(),
+            GraphData::BoxPlot(data) => data.len(),
+
+            // number of columns
+            GraphData::Heatmap { x_labels, .. } => x_labels.len(),
             GraphData::None => 0,
         }
     }
@@ -94,6 +177,9 @@ impl GraphData {
         match self {
             GraphData::Data1D(data) => data.is_empty(),
             GraphData::Data2D { data, .. } => data.is_empty(),
+            GraphData::TimeSeries(data) => data.is_empty(),
+            GraphData::BoxPlot(data) => data.is_empty(),
+            GraphData::Heatmap { data, .. } => data.is_empty(),
             GraphData::None => true,
         }
     }
@@ -110,19 +196,75 @@ impl Graph {
 
     /// It panics if it's not well-configured. If you're not sure, call `.is_valid` before calling this method
     pub fn draw(&self) -> String {
+        self.draw_lines().to_string(&self.color_mode)
+    }
+
+    /// It draws the graph, and tells where things are in the output. See `Layout`.
+    /// It panics if it's not well-configured, like `draw`.
+    pub fn render_with_metadata(&self) -> (String, Layout) {
+        let (lines, layout) = self.draw_lines_with_layout();
+
+        (lines.to_string(&self.color_mode), layout)
+    }
+
+    /// `Layout` of the output of `draw`. It draws the graph internally, so use `render_with_metadata` if you need both.
+    pub fn layout(&self) -> Layout {
+        self.draw_lines_with_layout().1
+    }
+
+    /// The output of `draw` as a `Canvas`, so that you can draw something on top of it.
+    /// The canvas has the `ColorMode` and the `Charset` of the graph. It panics if it's not well-configured, like `draw`.
+    pub fn draw_canvas(&self) -> Canvas {
+        Canvas::from_lines(self.draw_lines(), self.color_mode.clone(), self.charset.clone())
+    }
+
+    /// It draws the graph on `canvas` at (x, y), and returns the `Layout` of the graph in the coordinates of `canvas`.
+    /// The graph is cropped if it doesn't fit in `canvas`. It panics if it's not well-configured, like `draw`.
+    pub fn render_into(&self, canvas: &mut Canvas, x: usize, y: usize) -> Layout {
+        let (lines, mut layout) = self.draw_lines_with_layout();
+        canvas.get_lines_mut().blit_mut(&lines, x, y, None);
+        layout.shift(x, y);
+
+        layout
+    }
+
+    pub(crate) fn draw_lines(&self) -> Lines {
+        self.draw_lines_with_layout().0
+    }
+
+    pub(crate) fn draw_lines_with_layout(&self) -> (Lines, Layout) {
         match &self.data {
-            GraphData::Data1D(_) => self.draw_1d_graph(),
+            GraphData::Data1D(data) => self.draw_1d_graph(self.downsample_1d_data(data), None),
             GraphData::Data2D { .. } => self.draw_2d_graph(),
+            GraphData::BoxPlot(data) => self.draw_box_plot(data),
+            GraphData::Heatmap { data, x_labels, y_labels } => self.draw_heatmap(data, x_labels, y_labels),
+            GraphData::TimeSeries(data) => {
+                let (data, x_ticks) = self.resample_time_series_data(data);
+
+                self.draw_1d_graph(data, Some(x_ticks))
+            },
             GraphData::None => panic!("Cannot draw a graph without any data"),
         }
     }
 
+    pub(crate) fn get_orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    pub(crate) fn get_skip_value(&self) -> &SkipValue {
+        &self.skip_value
+    }
+
     pub(crate) fn get_actual_plot_width(&self) -> usize {
         match &self.data {
             GraphData::Data1D(_) => match self.block_width {
                 Some(w) => w * self.data.len(),
                 _ => self.plot_width,
             },
+            GraphData::BoxPlot(_) if self.orientation == Orientation::Vertical => match self.block_width {
+                Some(w) => w * self.data.len(),
+                _ => self.plot_width,
+            },
             _ => self.plot_width,
         }
     }
@@ -133,11 +275,13 @@ impl Graph {
     /// 4. If there're labeled_intervals, their interval must be valid.
     pub fn is_valid(&self) -> bool {
         (match (&self.y_min, &self.y_max) {  // why do I need to wrap it with parenthesis?
-            (Some(n), Some(m)) if n.gt_rat(&m) => false,
+            (Some(n), Some(m)) if n.gt_rat(m) => false,
             _ => true
         }) && match &self.data {
-            GraphData::Data1D(v) => v.len() > 0,
-            GraphData::Data2D { data, x_labels, y_labels } if x_labels.len() > 0 && y_labels.len() > 0 => {
+            GraphData::Data1D(v) => !v.is_empty(),
+            GraphData::TimeSeries(v) => !v.is_empty(),
+            GraphData::BoxPlot(v) => v.iter().any(|(_, stats)| stats.is_some()),
+            GraphData::Data2D { data, x_labels, y_labels } if !x_labels.is_empty() && !y_labels.is_empty() => {
                 let mut x_max = 0;
                 let mut y_max = 0;
 
@@ -155,6 +299,11 @@ impl Graph {
 
                 x_labels.len() >= x_max && y_labels.len() >= y_max && x_labels.len() == self.plot_width && y_labels.len() == self.plot_height
             },
+            GraphData::Heatmap { data, x_labels, y_labels } => {
+                !x_labels.is_empty() && data.len() == y_labels.len() && data.iter().all(|row| row.len() == x_labels.len())
+                && x_labels.len() == self.plot_width && y_labels.len() == self.plot_height
+                && self.heatmap_colors.as_ref().map(|colors| !colors.is_empty()).unwrap_or(true)
+            },
             _ => false,
         } && {
             self.labeled_intervals.iter().all(|i| i.is_valid())
@@ -164,434 +313,1193 @@ impl Graph {
         }
     }
 
-    fn draw_1d_graph(&self) -> String {
-        let mut data = self.data.unwrap_1d().clone();
-
+    // `data` is already downsampled or resampled.
+    // `x_ticks` are (column, label) of the x axis. If it's `None`, the labels of `data` are used instead.
+    fn draw_1d_graph(&self, data: &[(String, Ratio)], x_ticks: Option<&[(usize, String)]>) -> (Lines, Layout) {
+        let glyphs = self.charset.glyphs();
         let plot_width = self.get_actual_plot_width();
+        let (x_labels, x_label_columns) = self.draw_x_labels_with_breaks(data, x_ticks, plot_width);
 
-        if data.len() > plot_width * 2 {
-            data = pick_meaningful_values(&data, plot_width);
-        }
-
-        let (data_min, data_max, max_diff) = get_min_max_diff(&data, self.plot_height);
-        let (mut y_min, mut y_max) = unwrap_y_min_max(&self.y_min, &self.y_max, &data_min, &data_max);
-
-        let mut ratio_of_subgraphs = (3, 3);
+        self.draw_with_y_axis(
+            data,
+            plot_width,
+            x_labels,
+            &x_label_columns,
+            &|height, y_min, y_max, no_overflow_char| plot_1d(
+                data,
+                plot_width,
+                height,
+                y_min,
+                y_max,
+                no_overflow_char,
+                self.primary_color.clone(),
+                glyphs,
+            ),
+        )
+    }
 
-        let mut skip_range = match &self.skip_value {
-            _ if self.plot_height <= 18 => None,
-            SkipValue::None => None,
-            SkipValue::Automatic => {
-                if !max_diff.is_zero() && y_max.sub_rat(&y_min).div_rat(&max_diff).lt_i32(3) {
-                    let (y_min_, from, to, y_max_, ratio_of_subgraphs_) = get_where_to_skip(data.clone());
-                    ratio_of_subgraphs = ratio_of_subgraphs_;
+    fn draw_box_plot(&self, data: &[(String, Option<BoxStats>)]) -> (Lines, Layout) {
+        let glyphs = self.charset.glyphs();
+        let values = self.get_box_plot_values(data);
 
-                    // respect self.y_min and self.y_max if they're explicitly set
-                    if self.y_min.is_none() {
-                        y_min = y_min_;
-                    }
+        match self.orientation {
+            Orientation::Vertical => {
+                let plot_width = self.get_actual_plot_width();
+                let (x_labels, x_label_columns) = self.draw_x_labels_with_breaks(data, None, plot_width);
 
-                    if self.y_max.is_none() {
-                        y_max = y_max_;
-                    }
+                self.draw_with_y_axis(
+                    values,
+                    plot_width,
+                    x_labels,
+                    &x_label_columns,
+                    &|height, y_min, y_max, _| plot_boxes_vertical(
+                        data,
+                        plot_width,
+                        height,
+                        y_min,
+                        y_max,
+                        self.primary_color.clone(),
+                        glyphs,
+                    ),
+                )
+            },
+            Orientation::Horizontal => {
+                let (data_min, data_max) = get_min_max(values, self.plot_width);
+                let (x_min, x_max) = unwrap_y_min_max(&self.y_min, &self.y_max, &data_min, &data_max);
+
+                // there's a tick at every 10 characters
+                let ticks = (self.plot_width / 10).max(1);
+                let (x_min, x_max) = prettify_y_labels(
+                    &x_min,
+                    &x_max,
+                    ticks,
+                    self.pretty_y.as_ref().map(|n| (self.y_min.is_none(), self.y_max.is_none(), n.clone()))
+                );
+                let x_step = x_max.sub_rat(&x_min).div_i32(ticks as i32);
 
-                    let respect_skip_skip_range = if let Some((skip_skip_from, skip_skip_to)) = &self.skip_skip_range {
-                        match (skip_skip_from, skip_skip_to) {
-                            (Some(skip_skip_from), Some(skip_skip_to)) => if skip_skip_from.lt_rat(&from) && skip_skip_to.gt_rat(&to) {
-                                None
-                            } else {
-                                Some((from, to))
-                            },
-                            (None, Some(skip_skip_to)) => if skip_skip_to.gt_rat(&to) {
-                                None
-                            } else {
-                                Some((from, to))
-                            },
-                            (Some(skip_skip_from), None) => if skip_skip_from.lt_rat(&from) {
-                                None
-                            } else {
-                                Some((from, to))
-                            },
-                            // skip_skip_range is (inf, inf), which includes all the numbers
-                            (None, None) => None,
-                        }
-                    } else {
-                        Some((from, to))
-                    };
+                // `ticks + 1` ticks, so that both ends of the x axis are labeled. The last tick is at the last column,
+                // and its label is right-aligned to it (the labels don't touch the last column, like the other x labels).
+                let x_ticks = (0..=ticks).map(
+                    |tick| {
+                        let label = self.y_label_formatter.f(&x_min.add_rat(&x_step.mul_i32(tick as i32)));
+                        let column = (tick * self.plot_width / ticks).min(self.plot_width.saturating_sub(1));
 
-                    if let Some((from, to)) = respect_skip_skip_range {
-                        // if the explicitly set y_min and y_max are not compatible with the skipped range, it doesn't skip
-                        if y_min.lt_rat(&from) && to.lt_rat(&y_max) {
-                            Some((from, to))
+                        if tick == ticks {
+                            (column.saturating_sub(display_width(&label)), label)
                         }
 
                         else {
-                            None
+                            (column, label)
                         }
                     }
+                ).collect::<Vec<_>>();
+                let (x_labels, _) = draw_x_ticks(&x_ticks, self.plot_width, self.x_label_margin);
+
+                let mut y_labels = vec![None; self.plot_height];
 
-                    else {
-                        None
+                for (index, (label, _)) in data.iter().enumerate() {
+                    let start = (index * self.plot_height).div_ceil(data.len());
+                    let end = ((index + 1) * self.plot_height).div_ceil(data.len());
+
+                    if start < end {
+                        y_labels[(start + end - 1) / 2] = Some(label.clone());
                     }
                 }
 
-                else {
-                    None
-                }
+                let plot = plot_boxes_horizontal(
+                    data,
+                    self.plot_width,
+                    self.plot_height,
+                    &x_min,
+                    &x_max,
+                    self.primary_color.clone(),
+                    glyphs,
+                );
+
+                let (result, mut layout) = self.draw_2d_plot(plot, x_labels, &y_labels, &[]);
+                layout.x_range = Some((x_min, x_max));
+
+                (result, layout)
             },
-            SkipValue::Manual { from, to } => {
-                let mut values_below_skip_range = HashSet::new();
-                let mut values_above_skip_range = HashSet::new();
+        }
+    }
 
-                for (_, n) in data.iter() {
+    // It draws the y axis, the x labels, the labeled intervals, the title and everything else around the plot.
+    // `data` is used to decide the range of the y axis and the skip ranges. `plot_subplot` draws the plot area
+    // with the given (height, y_min, y_max, no_overflow_char). `x_label_columns` are the columns of the x labels on the first line, relative to the plot area.
+    fn draw_with_y_axis(
+        &self,
+        data: &[(String, Ratio)],
+        plot_width: usize,
+        x_labels: Lines,
+        x_label_columns: &[usize],
+        plot_subplot: &dyn Fn(usize, &Ratio, &Ratio, bool) -> Lines,
+    ) -> (Lines, Layout) {
+        let glyphs = self.charset.glyphs();
+        let subplots = self.get_subplots(data);
+
+        let mut y_labels = subplots.iter().map(
+            |(height, y_min, y_max)| self.fit_y_labels(draw_y_labels_1d_plot(
+                y_min,
+                y_max,
+                *height,
+                self.y_label_margin,
+                &self.y_label_formatter,
+            ))
+        ).collect::<Vec<_>>();
+
+        let y_labels_len = y_labels.iter().map(|y_labels| y_labels.get_width()).max().unwrap_or(0);
+
+        for y_labels in y_labels.iter_mut() {
+            if y_labels.get_width() < y_labels_len {
+                *y_labels = y_labels.add_padding([0, 0, y_labels_len - y_labels.get_width(), 0]);
+            }
+        }
 
-                    if n.lt_rat(from) {
-                        values_below_skip_range.insert(n.clone());
-                    }
+        let mut plot = Lines::empty();
+        let mut y_scales = Vec::with_capacity(subplots.len());
 
-                    else if n.gt_rat(to) {
-                        values_above_skip_range.insert(n.clone());
-                    }
+        // the top subplot is at the first row
+        let mut row = self.plot_height;
 
-                }
+        for (index, (height, y_min, y_max)) in subplots.iter().enumerate() {
+            let is_top = index + 1 == subplots.len();
 
-                if values_below_skip_range.len() * 2 > values_above_skip_range.len() * 3 {
-                    ratio_of_subgraphs = (4, 2);
-                }
+            y_scales.push(YScale {
+                rows: (row - height)..row,
+                y_min: y_min.clone(),
+                y_max: y_max.clone(),
+            });
 
-                else if values_above_skip_range.len() * 2 > values_below_skip_range.len() * 3 {
-                    ratio_of_subgraphs = (2, 4);
-                }
+            // `1` for the horizontal line
+            row = row.saturating_sub(height + 1);
 
-                Some((from.clone(), to.clone()))
-            },
-        };
+            let mut subplot = plot_subplot(
+                *height,
+                y_min,
+                y_max,
+                !is_top,  // no_overflow_char
+            );
+            subplot = subplot.add_border([false, index == 0, true, false], glyphs);
+            subplot = y_labels[index].merge_horizontally(&subplot, Alignment::First);
+
+            if index > 0 {
+                let mut horizontal_line = Lines::from_string(&glyphs.skip_line.to_string().repeat(subplot.get_width()), Alignment::First, &ColorMode::None);
+                horizontal_line.set_color_all(self.primary_color.clone());
 
-        if let Some((from, to)) = &skip_range {
-            if from.lt_rat(&y_min) || to.gt_rat(&y_max) {
-                skip_range = None;
+                plot = horizontal_line.merge_vertically(&plot, Alignment::First);
             }
+
+            plot = subplot.merge_vertically(&plot, Alignment::First);
         }
 
-        let y_labels_len;
+        let breaks = self.get_horizontal_breaks(plot_width);
 
-        let mut plot = match &skip_range {
-            None => {
-                let (y_min, y_max) = prettify_y_labels(
-                    &y_min,
-                    &y_max,
-                    self.plot_height,
-                    self.pretty_y.as_ref().map(|n| (self.y_min.is_none(), self.y_max.is_none(), n.clone()))
-                );
+        if !breaks.is_empty() {
+            // `+ 1` for the y axis
+            plot = apply_horizontal_breaks(&plot, y_labels_len + 1, &breaks, self.primary_color.clone(), glyphs);
+        }
 
-                let mut plot = plot_1d(
-                    &data,
-                    plot_width,
-                    self.plot_height,
-                    &y_min,
-                    &y_max,
-                    false,  // no_overflow_char
-                    self.primary_color.clone(),
-                );
-                plot = plot.add_border([false, true, true, false]);
+        let width_with_breaks = apply_breaks_to_column(plot_width, &breaks).0;
 
-                let y_labels = draw_y_labels_1d_plot(
-                    &y_min,
-                    &y_max,
-                    self.plot_height,
-                    self.y_label_margin,
-                    &self.y_label_formatter,
-                );
-                y_labels_len = y_labels.get_width();
+        // time series are resampled, so that each column has a value
+        let data_len = match &self.data {
+            GraphData::TimeSeries(_) => plot_width,
+            _ => self.data.len(),
+        };
 
-                y_labels.merge_horizontally(&plot, Alignment::First)
+        let mut layout = Layout {
+            plot_area: Rect {
+                x: 0,
+                y: 0,
+                width: width_with_breaks,
+                height: self.plot_height,
             },
-            Some((from, to)) => {
-                let (mut height1, mut height2) = (
-                    self.plot_height * ratio_of_subgraphs.0 / 6,
-                    self.plot_height * ratio_of_subgraphs.1 / 6,
-                );
+            skipped_ranges: subplots.windows(2).map(
+                |subplots| (subplots[0].2.clone(), subplots[1].1.clone())
+            ).collect(),
+            y_scales,
+            columns: get_data_columns(data_len, plot_width).into_iter().map(
+                |columns| {
+                    // if it's hidden, it's at the break line
+                    let hidden_at = apply_breaks_to_column(columns.start, &breaks).0;
+
+                    let visible = columns.filter_map(
+                        |x| match apply_breaks_to_column(x, &breaks) {
+                            (x, true) => Some(x),
+                            (_, false) => None,
+                        }
+                    ).collect::<Vec<_>>();
 
-                // it has to be height1 + height2 + 1 == self.plot_height
-                // 1 is for the delimiter line
-                if height1 > height2 {
-                    height1 += self.plot_height - height1 - height2;
-                    height2 -= 1;
+                    match (visible.first(), visible.last()) {
+                        (Some(first), Some(last)) => *first..(last + 1),
+                        _ => hidden_at..hidden_at,
+                    }
                 }
+            ).collect(),
+            horizontal_breaks: breaks.iter().map(
+                |(from, _)| {
+                    let x = apply_breaks_to_column(*from, &breaks).0;
 
-                else {
-                    height2 += self.plot_height - height1 - height2;
-                    height1 -= 1;
+                    x..(x + 2)
+                }
+            ).collect(),
+            ..Layout::default()
+        };
+        layout.shift(y_labels_len + 1, 0);
+
+        // the intervals that start or end at hidden data start or end at the break lines
+        let intervals = self.labeled_intervals.iter().map(
+            |interval| interval.map_coordinate(
+                |x, is_end| match apply_breaks_to_column(x, &breaks) {
+                    _ if x >= plot_width => width_with_breaks + x - plot_width,
+                    (x, true) => x,
+                    (x, false) => if is_end { x + 1 } else { x },
                 }
+            )
+        ).collect::<Vec<_>>();
+
+        self.highlight_labeled_intervals(&mut plot, &intervals, &layout.plot_area);
+
+        if self.gridlines != Gridlines::None {
+            // the rows of the y labels of each subplot
+            let rows = layout.y_scales.iter().flat_map(
+                |y_scale| y_scale.rows.clone().step_by(self.y_label_margin.max(1))
+            ).collect::<Vec<_>>();
+            let columns = x_label_columns.iter().map(|x| layout.plot_area.x + x).collect::<Vec<_>>();
+
+            draw_gridlines(
+                &mut plot,
+                self.gridlines,
+                &rows,
+                &columns,
+                &layout.plot_area,
+                self.gridline_color.clone(),
+                glyphs,
+            );
+        }
 
-                // if y_min, y_max, or skip_value is explicitly set by the user, it never touches them
-                // otherwise it tries to adjust them for prettier y_labels
-                let (plot1_y_min, plot1_y_max) = prettify_y_labels(
-                    &y_min,
-                    &from,
-                    height1,
-                    self.pretty_y.as_ref().map(|n| (self.y_min.is_none(), self.skip_value.is_automatic(), n.clone()))
-                );
+        if !self.annotations.is_empty() {
+            let annotations = self.get_annotation_cells(data, &layout);
+            draw_annotations(&mut plot, &annotations, &layout.plot_area, glyphs);
+        }
 
-                let mut plot1 = plot_1d(
-                    &data,
-                    plot_width,
-                    height1,
-                    &plot1_y_min,
-                    &plot1_y_max,
-                    true,  // no_overflow_char
-                    self.primary_color.clone(),
-                );
-                plot1 = plot1.add_border([false, true, true, false]);
+        plot = plot.merge_vertically(&x_labels, Alignment::Last);
 
-                let (plot2_y_min, plot2_y_max) = prettify_y_labels(
-                    &to,
-                    &y_max,
-                    height2,
-                    self.pretty_y.as_ref().map(|n| (self.skip_value.is_automatic(), self.y_max.is_none(), n.clone()))
-                );
+        if !intervals.is_empty() {
+            let arrows = draw_labeled_intervals(&intervals, width_with_breaks, glyphs);
+            plot = plot.merge_vertically(&arrows, Alignment::Last);
+        }
 
-                let mut plot2 = plot_1d(
-                    &data,
-                    plot_width,
-                    height2,
-                    &plot2_y_min,
-                    &plot2_y_max,
-                    false,  // no_overflow_char
-                    self.primary_color.clone(),
-                );
-                plot2 = plot2.add_border([false, false, true, false]);
-
-                let mut y_labels1 = draw_y_labels_1d_plot(
-                    &plot1_y_min,
-                    &plot1_y_max,
-                    height1,
-                    self.y_label_margin,
-                    &self.y_label_formatter,
-                );
-                let mut y_labels2 = draw_y_labels_1d_plot(
-                    &plot2_y_min,
-                    &plot2_y_max,
-                    height2,
-                    self.y_label_margin,
-                    &self.y_label_formatter,
-                );
+        let plot = self.draw_around_plot(plot, &[], &mut layout);
 
-                if y_labels1.get_width() < y_labels2.get_width() {
-                    y_labels1 = y_labels1.add_padding([0, 0, y_labels2.get_width() - y_labels1.get_width(), 0]);
-                }
+        (plot, layout)
+    }
 
-                else if y_labels2.get_width() < y_labels1.get_width() {
-                    y_labels2 = y_labels2.add_padding([0, 0, y_labels1.get_width() - y_labels2.get_width(), 0]);
-                }
+    fn draw_2d_graph(&self) -> (Lines, Layout) {
+        let (
+            data, x_labels, y_labels
+        ) = self.data.unwrap_2d();
+        let glyphs = self.charset.glyphs();
 
-                y_labels_len = y_labels1.get_width();
-                plot1 = y_labels1.merge_horizontally(&plot1, Alignment::First);
-                plot2 = y_labels2.merge_horizontally(&plot2, Alignment::First);
+        let plot = plot_2d(data, self.plot_width, self.plot_height, glyphs);
 
-                let mut horizontal_line = Lines::from_string(&"~".repeat(plot1.get_width()), Alignment::First, &ColorMode::None);
-                horizontal_line.set_color_all(self.primary_color.clone());
+        let x_labels = draw_x_labels_2d_plot(x_labels, self.plot_width, self.x_label_margin);
+
+        self.draw_2d_plot(plot, x_labels, y_labels, &[])
+    }
+
+    fn draw_heatmap(&self, data: &[Vec<Ratio>], x_labels: &[Option<String>], y_labels: &[Option<String>]) -> (Lines, Layout) {
+        let glyphs = self.charset.glyphs();
+        let mut data_min = &data[0][0];
+        let mut data_max = &data[0][0];
+
+        for n in data.iter().flatten() {
+            if n.lt_rat(data_min) {
+                data_min = n;
+            }
+
+            if n.gt_rat(data_max) {
+                data_max = n;
+            }
+        }
+
+        let (y_min, y_max) = unwrap_y_min_max(&self.y_min, &self.y_max, data_min, data_max);
+
+        // (glyph, color) of each level, from the lowest to the highest
+        let levels = match &self.heatmap_colors {
+            Some(colors) => {
+                // with `ColorMode::TerminalBg`, the background of the cell is colored
+                let glyph = if let ColorMode::TerminalBg = self.color_mode { ' ' } else { glyphs.bars[0] };
+
+                colors.iter().map(|color| (glyph, Some(color.clone()))).collect()
+            },
+            None => glyphs.shades.iter().map(|glyph| (*glyph, self.primary_color.clone())).collect::<Vec<_>>(),
+        };
+
+        let plot = plot_heatmap(data, &y_min, &y_max, &levels);
+        let scale = get_heatmap_scale(&y_min, &y_max, &levels, &self.y_label_formatter);
+
+        let x_labels = draw_x_labels_2d_plot(x_labels, self.plot_width, self.x_label_margin);
 
-                plot1 = horizontal_line.merge_vertically(&plot1, Alignment::First);
+        self.draw_2d_plot(plot, x_labels, y_labels, &scale)
+    }
 
-                plot2.merge_vertically(&plot1, Alignment::First)
+    // `plot` has no axis and no labels. `scale` is the scale of heatmaps, and it's empty for the other graphs.
+    fn draw_2d_plot(&self, mut plot: Lines, x_labels: Lines, y_labels: &[Option<String>], scale: &[LegendEntry]) -> (Lines, Layout) {
+        let glyphs = self.charset.glyphs();
+        let mut layout = Layout {
+            plot_area: Rect {
+                x: 0,
+                y: 0,
+                width: plot.get_width(),
+                height: plot.get_height(),
             },
+            columns: (0..plot.get_width()).map(|x| x..(x + 1)).collect(),
+            ..Layout::default()
+        };
+
+        // the labeled intervals of 2-dimensional data and heatmaps are in the x coordinates,
+        // and horizontal box plots don't have them because their x axis is the values
+        let intervals = match &self.data {
+            GraphData::Data2D { .. }
+            | GraphData::Heatmap { .. } => self.labeled_intervals.as_slice(),
+            _ => &[],
         };
 
-        let x_labels = draw_x_labels(&data, plot_width, self.x_label_margin);
+        self.highlight_labeled_intervals(&mut plot, intervals, &layout.plot_area);
+
+        plot = plot.add_border([false, true, true, false], glyphs);
         plot = plot.merge_vertically(&x_labels, Alignment::Last);
 
-        if !self.labeled_intervals.is_empty() {
-            let arrows = draw_labeled_intervals(&self.labeled_intervals, plot_width);
+        if !intervals.is_empty() {
+            let arrows = draw_labeled_intervals(intervals, layout.plot_area.width, glyphs);
             plot = plot.merge_vertically(&arrows, Alignment::Last);
         }
 
-        if let Some((from, to)) = self.horizontal_break {
-            let plot_width = plot.get_width();
-            let plot_height = plot.get_height();
-            let left_half = plot.crop(0, 0, y_labels_len + from, plot_height);
-            let right_half = plot.crop(y_labels_len + to, 0, plot_width - y_labels_len - to, plot_height);
-            let break_line = draw_vertial_line(plot_height, self.primary_color.clone());
+        let y_labels = draw_y_labels_2d_plot(y_labels);
+        layout.shift(y_labels.get_width() + 1, 0);
+        plot = y_labels.merge_horizontally(&plot, Alignment::First);
+
+        let plot = self.draw_around_plot(plot, scale, &mut layout);
+
+        (plot, layout)
+    }
+
+    // It applies `self.interval_highlight` to `area` of `plot`. The columns of `intervals` are relative to `area`.
+    fn highlight_labeled_intervals(&self, plot: &mut Lines, intervals: &[Interval], area: &Rect) {
+        highlight_labeled_intervals(
+            plot,
+            intervals,
+            area,
+            self.interval_highlight,
+            self.interval_highlight_color.clone(),
+            &self.color_mode,
+            self.charset.glyphs(),
+        );
+    }
 
-            plot = left_half.merge_horizontally(&break_line, Alignment::Last);
-            plot = plot.merge_horizontally(&right_half, Alignment::Last);
+    // It draws the axis labels, the legend, the title and the paddings around the plot, and moves `layout` accordingly.
+    // `scale` is the scale of heatmaps, which is drawn on the right side even if the graph doesn't have a legend.
+    fn draw_around_plot(&self, mut plot: Lines, scale: &[LegendEntry], layout: &mut Layout) -> Lines {
+        let glyphs = self.charset.glyphs();
+        let (legend, legend_position) = match &self.legend {
+            Some(legend) => {
+                let entries = self.get_legend_entries(legend, scale, layout);
+
+                (
+                    (!entries.is_empty()).then(|| draw_legend(&entries, legend.position, glyphs)),
+                    legend.position,
+                )
+            },
+            None if !scale.is_empty() => (Some(draw_legend(scale, LegendPosition::Right, glyphs)), LegendPosition::Right),
+            None => (None, LegendPosition::Right),
+        };
+        let xal = self.x_axis_label.as_ref().map(|xal| Lines::from_string(xal, Alignment::First, &ColorMode::None));
+        let yal = self.y_axis_label.as_ref().map(|yal| Lines::from_string(yal, Alignment::First, &ColorMode::None));
+        let title = self.title.as_ref().map(|t| draw_title(t, self.big_title, self.title_color.clone(), glyphs));
+
+        // It decides where each part goes, and draws them on a buffer at once.
+        // (part, x, y) of the body, which is everything below the title. The x axis label and the legend are
+        // on the right side of the plot, and the y axis label is above them.
+        let mut body_parts = vec![];
+        let mut body_width = plot.get_width();
+        let mut body_height = plot.get_height();
+
+        if let Some(xal) = &xal {
+            body_parts.push((xal, body_width, self.plot_height));
+            body_width += xal.get_width();
+            body_height = body_height.max(self.plot_height + xal.get_height());
         }
 
-        if let Some(xal) = &self.x_axis_label {
-            let mut xal = Lines::from_string(xal, Alignment::First, &ColorMode::None);
-            xal = xal.add_padding([self.plot_height, 0, 0, 0]);
-            plot = plot.merge_horizontally(&xal, Alignment::First);
+        match (&legend, legend_position) {
+            (Some(legend), LegendPosition::Right) => {
+                layout.legend = Some(Rect { x: body_width + 2, y: 0, width: legend.get_width(), height: legend.get_height() });
+                body_parts.push((legend, body_width + 2, 0));
+                body_width += legend.get_width() + 2;
+                body_height = body_height.max(legend.get_height());
+            },
+
+            // below everything, and it starts at the plot area
+            (Some(legend), LegendPosition::Bottom) => {
+                let x = layout.plot_area.x;
+
+                layout.legend = Some(Rect { x, y: body_height, width: legend.get_width(), height: legend.get_height() });
+                body_parts.push((legend, x, body_height));
+                body_width = body_width.max(x + legend.get_width());
+                body_height += legend.get_height();
+            },
+            (Some(legend), corner) => {
+                let area = layout.plot_area;
+                let (width, height) = (legend.get_width().min(area.width), legend.get_height().min(area.height));
+                let x = match corner {
+                    LegendPosition::TopLeft | LegendPosition::BottomLeft => area.x,
+                    _ => area.x + area.width - width,
+                };
+                let y = match corner {
+                    LegendPosition::TopLeft | LegendPosition::TopRight => area.y,
+                    _ => area.y + area.height - height,
+                };
+
+                layout.legend = Some(Rect { x, y, width, height });
+                plot.blit_region_mut(legend, (0, 0, width, height), x, y, None);
+            },
+            (None, _) => {},
         }
 
-        if let Some(yal) = &self.y_axis_label {
-            let yal = Lines::from_string(yal, Alignment::First, &ColorMode::None);
-            plot = yal.merge_vertically(&plot, Alignment::First);
+        body_parts.push((&plot, 0, 0));
+
+        if let Some(yal) = &yal {
+            layout.shift(0, yal.get_height());
+
+            for (_, _, y) in body_parts.iter_mut() {
+                *y += yal.get_height();
+            }
+
+            body_parts.push((yal, 0, 0));
+            body_width = body_width.max(yal.get_width());
+            body_height += yal.get_height();
         }
 
-        if let Some(t) = &self.title {
-            let title = draw_title(t, self.big_title, self.title_color.clone());
-            plot = title.merge_vertically(&plot, Alignment::Center);
+        let (width, title_height) = match &title {
+            Some(title) => (body_width.max(title.get_width()), title.get_height()),
+            None => (body_width, 0),
+        };
+
+        // the extra space goes to the left side first
+        let body_x = (width - body_width) / 2 + (width - body_width) % 2;
+
+        if title.is_some() {
+            layout.shift(body_x, title_height);
         }
 
-        plot = plot.add_padding(self.paddings);
+        layout.shift(self.paddings[2], self.paddings[0]);
+
+        let mut result = Lines::new(
+            width + self.paddings[2] + self.paddings[3],
+            title_height + body_height + self.paddings[0] + self.paddings[1],
+        );
+
+        if let Some(title) = &title {
+            let title_x = (width - title.get_width()) / 2 + (width - title.get_width()) % 2;
+            result.blit_mut(title, self.paddings[2] + title_x, self.paddings[0], None);
+        }
 
-        plot.to_string(&self.color_mode)
+        for (part, x, y) in body_parts.into_iter() {
+            result.blit_mut(part, self.paddings[2] + body_x + x, self.paddings[0] + title_height + y, None);
+        }
+
+        layout.width = result.get_width();
+        layout.height = result.get_height();
+
+        result
     }
 
-    fn draw_2d_graph(&self) -> String {
-        let (
-            data, x_labels, y_labels
-        ) = self.data.unwrap_2d();
+    // The entries of `legend`, in the order that's documented in `Legend`. `scale` is the scale of heatmaps.
+    fn get_legend_entries(&self, legend: &Legend, scale: &[LegendEntry], layout: &Layout) -> Vec<LegendEntry> {
+        if !legend.auto_entries {
+            return legend.entries.clone();
+        }
 
-        let mut plot = plot_2d(&data, self.plot_width, self.plot_height);
-        plot = plot.add_border([false, true, true, false]);
+        let glyphs = self.charset.glyphs();
+        let mut entries = vec![];
+        let data_label = legend.data_label.clone().or_else(|| self.y_axis_label.clone()).unwrap_or_else(|| String::from("data"));
 
-        let x_labels = draw_x_labels(
-            &x_labels.iter().map(
-                |s| (
-                    match s { Some(s) => s.to_string(), _ => String::new() },
-                    ()
-                )
-            ).collect(),
-            self.plot_width,
-            self.x_label_margin
-        );
-        plot = plot.merge_vertically(&x_labels, Alignment::Last);
+        match &self.data {
+            GraphData::Data1D(_)
+            | GraphData::TimeSeries(_) => {
+                entries.push(LegendEntry { glyph: glyphs.bars[0], color: self.primary_color.clone(), label: data_label });
+            },
+            GraphData::BoxPlot(data) => {
+                entries.push(LegendEntry { glyph: glyphs.tees[0], color: self.primary_color.clone(), label: data_label });
 
-        let y_labels = draw_y_labels_2d_plot(y_labels);
-        plot = y_labels.merge_horizontally(&plot, Alignment::First);
+                if data.iter().any(|(_, stats)| stats.as_ref().map(|stats| !stats.outliers.is_empty()).unwrap_or(false)) {
+                    entries.push(LegendEntry { glyph: glyphs.outlier, color: self.primary_color.clone(), label: String::from("outliers") });
+                }
+            },
+            _ => {},
+        }
+
+        if !layout.skipped_ranges.is_empty() {
+            entries.push(LegendEntry { glyph: glyphs.skip_line, color: None, label: String::from("skipped range") });
+        }
 
-        if let Some(xal) = &self.x_axis_label {
-            let mut xal = Lines::from_string(xal, Alignment::First, &ColorMode::None);
-            xal = xal.add_padding([self.plot_height, 0, 0, 0]);
-            plot = plot.merge_horizontally(&xal, Alignment::First);
+        if !layout.horizontal_breaks.is_empty() {
+            entries.push(LegendEntry { glyph: glyphs.horizontal_break[0], color: self.primary_color.clone(), label: String::from("hidden data") });
         }
 
-        if let Some(yal) = &self.y_axis_label {
-            let yal = Lines::from_string(yal, Alignment::First, &ColorMode::None);
-            plot = yal.merge_vertically(&plot, Alignment::First);
+        // horizontal box plots don't have labeled intervals
+        let has_intervals = !self.labeled_intervals.is_empty()
+            && !matches!((&self.data, self.orientation), (GraphData::BoxPlot(_), Orientation::Horizontal));
+
+        if has_intervals && self.interval_highlight != IntervalHighlight::None {
+            let glyph = match self.interval_highlight {
+                IntervalHighlight::Boundaries => glyphs.dotted_vertical,
+                _ => glyphs.shades[0],
+            };
+            let label = self.labeled_intervals.iter().map(|interval| interval.label_to_string()).collect::<Vec<_>>().join(", ");
+
+            entries.push(LegendEntry { glyph, color: self.interval_highlight_color.clone(), label });
         }
 
-        if let Some(t) = &self.title {
-            let title = draw_title(t, self.big_title, self.title_color.clone());
-            plot = title.merge_vertically(&plot, Alignment::Center);
+        for annotation in self.annotations.iter() {
+            if let Some(label) = &annotation.label {
+                let glyph = match annotation.style {
+                    AnnotationStyle::VerticalLine => glyphs.vertical,
+                    _ => glyphs.marker,
+                };
+
+                entries.push(LegendEntry { glyph, color: annotation.color.clone(), label: label.clone() });
+            }
         }
 
-        plot = plot.add_padding(self.paddings);
+        entries.extend_from_slice(scale);
+        entries.extend_from_slice(&legend.entries);
 
-        plot.to_string(&self.color_mode)
+        entries
     }
 
-    fn adjust_all_labeled_intervals(&mut self) {
-        let plot_width = self.get_actual_plot_width();
+    // (from, to) of the hidden columns of the plot, `to` is exclusive.
+    // They're sorted, and the overlapping breaks are merged.
+    fn get_horizontal_breaks(&self, plot_width: usize) -> Vec<(usize, usize)> {
         let data_len = self.data.len();
 
-        if !self.data.is_empty() {
-            self.labeled_intervals.iter_mut().for_each(
-                |i| i.adjust_coordinate(plot_width, data_len)
-            );
+        if data_len == 0 {
+            return vec![];
         }
+
+        let mut columns = self.horizontal_breaks.iter().map(
+            |(start, end)| match &self.data {
+                // the columns of time series are decided by the timestamps, not the indices
+                GraphData::TimeSeries(series) => (
+                    self.get_time_series_column(series, (*start).min(data_len) as i32).min(plot_width),
+                    (self.get_time_series_column(series, (*end).min(data_len) as i32) + 1).min(plot_width),
+                ),
+                _ => (start.min(&data_len) * plot_width / data_len, (end + 1).min(data_len) * plot_width / data_len),
+            }
+        ).filter(
+            |(from, to)| from < to
+        ).collect::<Vec<_>>();
+        columns.sort_unstable();
+
+        let mut result: Vec<(usize, usize)> = Vec::with_capacity(columns.len());
+
+        for (from, to) in columns.into_iter() {
+            match result.last_mut() {
+                Some((_, last_to)) if from <= *last_to => {
+                    *last_to = to.max(*last_to);
+                },
+                _ => {
+                    result.push((from, to));
+                },
+            }
+        }
+
+        result
     }
-}
 
-fn pick_meaningful_values(data: &Vec<(String, Ratio)>, width: usize) -> Vec<(String, Ratio)> {
-    // a graph with odd-sized width is not supported because of this line
-    let half_width = width / 2;
+    // If there're horizontal breaks, the labels of the hidden data are not drawn,
+    // and the labels are placed at the columns after the breaks are applied.
+    // (labels, the columns where the labels on the first line start), after the horizontal breaks are applied
+    fn draw_x_labels_with_breaks<T>(&self, data: &[(String, T)], x_ticks: Option<&[(usize, String)]>, plot_width: usize) -> (Lines, Vec<usize>) {
+        let breaks = self.get_horizontal_breaks(plot_width);
+
+        if breaks.is_empty() {
+            return match x_ticks {
+                Some(x_ticks) => draw_x_ticks(x_ticks, plot_width, self.x_label_margin),
+                None => draw_x_labels(data, plot_width, self.x_label_margin),
+            };
+        }
 
-    let mut last_ind = 0;
-    let mut result = Vec::with_capacity(width);
+        let x_ticks = match x_ticks {
+            Some(x_ticks) => x_ticks.to_vec(),
 
-    for i in 0..half_width {
-        let curr_ind = (i + 1) * data.len() / half_width;
-        let mut min_ind = 0;
-        let mut min_val = &data[last_ind].1;
-        let mut max_ind = 0;
-        let mut max_val = &data[last_ind].1;
+            // a tick at the first column of each data
+            None => (0..plot_width).filter(
+                |x| *x == 0 || x * data.len() / plot_width != (x - 1) * data.len() / plot_width
+            ).map(
+                |x| (x, data[x * data.len() / plot_width].0.replace('\n', " "))
+            ).collect(),
+        };
 
-        for (ind, (_, val)) in data[last_ind..curr_ind].iter().enumerate() {
-            if val.gt_rat(&max_val) {
-                max_ind = ind;
-                max_val = val;
+        let x_ticks = x_ticks.into_iter().filter_map(
+            |(x, label)| match apply_breaks_to_column(x, &breaks) {
+                (x, true) => Some((x, label)),
+                (_, false) => None,
             }
+        ).collect::<Vec<_>>();
+
+        draw_x_ticks(&x_ticks, apply_breaks_to_column(plot_width, &breaks).0, self.x_label_margin)
+    }
+
+    // it applies `self.show_y_labels` and `self.y_label_width`
+    fn fit_y_labels(&self, y_labels: Lines) -> Lines {
+        if !self.show_y_labels {
+            Lines::new(0, y_labels.get_height())
+        }
+
+        else if let Some(width) = self.y_label_width.filter(|width| *width > y_labels.get_width()) {
+            y_labels.add_padding([0, 0, width - y_labels.get_width(), 0])
+        }
+
+        else {
+            y_labels
+        }
+    }
+
+    // 1-dimensional data (`self.data`), downsampled to the plot width. The per-point work is done in the numeric type of `data`,
+    // and only the result is converted to `Ratio`. It's cached until the data or the width changes.
+    fn downsample_1d_data<'a>(&'a self, data: &'a Series<String>) -> &'a [(String, Ratio)] {
+        let downsampled = self.cache.downsampled.get_or_init(
+            || {
+                let plot_width = self.get_actual_plot_width();
+
+                match data {
+                    Series::Exact(data) => self.downsampling.downsample(data, plot_width),
+                    Series::F64(data) => {
+                        let downsampled = self.downsampling.downsample(data, plot_width);
 
-            else if val.lt_rat(&min_val) {
-                min_ind = ind;
-                min_val = val;
+                        Some(downsampled.as_deref().unwrap_or(data).iter().map(|(label, n)| (label.clone(), n.to_ratio())).collect())
+                    },
+                }
             }
+        );
+
+        match (downsampled, data) {
+            (Some(downsampled), _) => downsampled,
+            (None, Series::Exact(data)) => data,
+
+            // f64 data is always converted
+            (None, Series::F64(_)) => unreachable!(),
         }
+    }
+
+    // (resampled data, x ticks) of time series (`self.data`). It's cached until the data, the width or the time range changes.
+    #[allow(clippy::type_complexity)]
+    fn resample_time_series_data(&self, data: &Series<i64>) -> &(Vec<(String, Ratio)>, Vec<(usize, String)>) {
+        self.cache.resampled.get_or_init(
+            || {
+                let time_range = self.get_time_range(data);
 
-        if min_ind < max_ind {
-            result.push(data[last_ind + min_ind].clone());
-            result.push(data[last_ind + max_ind].clone());
+                match data {
+                    Series::Exact(data) => resample_time_series(data, time_range, self.plot_width, self.x_label_margin),
+                    Series::F64(data) => resample_time_series(data, time_range, self.plot_width, self.x_label_margin),
+                }
+            }
+        )
+    }
+
+    // (category, value) of all the values of the boxes of `self.data`
+    fn get_box_plot_values(&self, data: &[(String, Option<BoxStats>)]) -> &[(String, Ratio)] {
+        self.cache.box_plot_values.get_or_init(
+            || data.iter().flat_map(
+                |(label, stats)| stats.iter().flat_map(
+                    |stats| stats.values().into_iter().map(|n| (label.clone(), n))
+                )
+            ).collect()
+        )
+    }
+
+    // `data` must be `get_y_axis_data`, because the statistics are cached with it.
+    fn get_data_stats(&self, data: &[(String, Ratio)]) -> &DataStats {
+        self.cache.stats.get_or_init(|| DataStats::new(data))
+    }
+
+    // The setters that change the data, or anything that downsampling and resampling depend on, must call it.
+    pub(crate) fn clear_cache(&mut self) {
+        self.cache = DataCache::default();
+    }
+
+    // (from, to) of the x axis of time series
+    fn get_time_range(&self, data: &Series<i64>) -> (i64, i64) {
+        self.time_range.unwrap_or((*data.get_key(0), *data.get_key(data.len() - 1)))
+    }
+
+    // (annotation, x, y) of the cells that the annotations point at, in the coordinates of `layout`.
+    // `data` is what `plot_1d` draws, and it's used for the annotations without values.
+    // The annotations whose data don't exist or are hidden by horizontal breaks are not included.
+    fn get_annotation_cells(&self, data: &[(String, Ratio)], layout: &Layout) -> Vec<(&Annotation, usize, usize)> {
+        let plot_width = self.get_actual_plot_width();
+
+        self.annotations.iter().filter_map(
+            |annotation| {
+                // the index of `layout.columns`
+                let index = match &self.data {
+                    GraphData::TimeSeries(series) if annotation.index < series.len() => get_column(
+                        *series.get_key(annotation.index),
+                        self.get_time_range(series),
+                        plot_width,
+                    )?,
+                    GraphData::TimeSeries(_) => { return None; },
+                    _ => annotation.index,
+                };
+                let columns = layout.columns.get(index)?;
+
+                if columns.is_empty() {
+                    return None;
+                }
+
+                let y = match (annotation.style, &annotation.value, &self.data) {
+                    (AnnotationStyle::VerticalLine, _, _) => layout.plot_area.y,
+                    (_, Some(value), _) => layout.get_row(value)?,
+                    (_, None, GraphData::BoxPlot(data)) => layout.get_row(&data[index].1.as_ref()?.median)?,
+
+                    // the value that's drawn at the column, like `pick`
+                    (_, None, _) => {
+                        let column = get_data_columns(layout.columns.len(), plot_width)[index].start;
+
+                        layout.get_row(&data[column * data.len() / plot_width].1)?
+                    },
+                };
+
+                Some((annotation, (columns.start + columns.end - 1) / 2, y))
+            }
+        ).collect()
+    }
+
+    // the values that decide the y axis, after downsampling or resampling. It's `None` if the graph doesn't have a y axis,
+    // or if it doesn't have any data. They're cached, so it's cheap to call it again.
+    fn get_y_axis_data(&self) -> Option<&[(String, Ratio)]> {
+        if self.data.is_empty() {
+            return None;
+        }
+
+        let data = match &self.data {
+            GraphData::Data1D(data) => self.downsample_1d_data(data),
+            GraphData::TimeSeries(data) => &self.resample_time_series_data(data).0,
+            GraphData::BoxPlot(data) if self.orientation == Orientation::Vertical => self.get_box_plot_values(data),
+            _ => { return None; },
+        };
+
+        // box plots whose categories have no samples
+        if data.is_empty() {
+            None
         }
 
         else {
-            result.push(data[last_ind + max_ind].clone());
-            result.push(data[last_ind + min_ind].clone());
+            Some(data)
         }
+    }
+
+    // (y_min, y_max) that the engine would choose when `self.y_min` and `self.y_max` are not set,
+    // before it's prettified. It's `None` if the graph doesn't have a y axis.
+    pub(crate) fn get_data_y_range(&self) -> Option<(Ratio, Ratio)> {
+        let values = self.get_y_axis_data()?;
 
-        last_ind = curr_ind;
+        Some(self.get_data_min_max(values))
     }
 
-    result
-}
+    // (min, max) of `data` with a little margin. If `data` is not downsampled, it uses `self.data_range`.
+    fn get_data_min_max(&self, data: &[(String, Ratio)]) -> (Ratio, Ratio) {
+        match (&self.data_range, &self.data) {
+            (Some((data_min, data_max)), GraphData::Data1D(raw)) if raw.len() == data.len() => add_margin_to_min_max(data_min, data_max, self.plot_height),
+            _ => get_min_max(data, self.plot_height),
+        }
+    }
+
+    /// The ranges of the y axis that are not drawn, from the bottom to the top. It's empty if the graph doesn't skip anything,
+    /// or if it doesn't have a y axis. The ranges are after prettified, so they're exactly what the y labels show.
+    pub fn get_skipped_ranges(&self) -> Vec<(Ratio, Ratio)> {
+        match self.get_y_axis_data() {
+            Some(data) => self.get_subplots(data).windows(2).map(
+                |subplots| (subplots[0].2.clone(), subplots[1].1.clone())
+            ).collect(),
+            _ => vec![],
+        }
+    }
+
+    // (height, y_min, y_max) of each subplot, from the bottom to the top. The heights don't include
+    // the horizontal lines between the subplots. If it doesn't skip anything, there's only one subplot.
+    fn get_subplots(&self, data: &[(String, Ratio)]) -> Vec<(usize, Ratio, Ratio)> {
+        let policy = &self.skip_policy;
+
+        // it sorts the values only if it might skip ranges
+        let stats = match &self.skip_value {
+            _ if self.plot_height < policy.min_plot_height => None,
+            SkipValue::None => None,
+            _ => Some(self.get_data_stats(data)),
+        };
+        let (data_min, data_max) = match stats {
+            Some(stats) => stats.get_min_max(self.plot_height),
+            None => self.get_data_min_max(data),
+        };
+        let (mut y_min, mut y_max) = unwrap_y_min_max(&self.y_min, &self.y_max, &data_min, &data_max);
+
+        // sorted, and they don't overlap
+        let mut skip_ranges = match (&self.skip_value, stats) {
+            (_, None) => vec![],
+            (SkipValue::None, _) => vec![],
+            (SkipValue::Automatic, Some(stats)) => {
+                let max_diff = stats.get_max_gap();
 
-fn get_where_to_skip(mut data: Vec<(String, Ratio)>) -> (Ratio, Ratio, Ratio, Ratio, (usize, usize)) {
-    let mut curr_max_diff = Ratio::zero();
-    let mut curr_max_diff_ind = 0;
-    data.sort_unstable_by_key(|(_, n)| n.clone());
+                if !max_diff.is_zero() && y_max.sub_rat(&y_min).lt_rat(&max_diff.mul_rat(&policy.min_gap_ratio)) {
+                    let (y_min_, ranges, y_max_) = get_where_to_skip(stats, policy.get_max_ranges(self.plot_height), policy);
 
-    for i in 0..(data.len() - 1) {
-        let curr_diff = data[i + 1].1.sub_rat(&data[i].1);
+                    // respect self.y_min and self.y_max if they're explicitly set
+                    if self.y_min.is_none() {
+                        y_min = y_min_;
+                    }
+
+                    if self.y_max.is_none() {
+                        y_max = y_max_;
+                    }
 
-        if curr_diff.gt_rat(&curr_max_diff) {
-            curr_max_diff = curr_diff;
-            curr_max_diff_ind = i;
+                    ranges.into_iter().filter(
+                        |(from, to)| policy.allows(from, to)
+                    ).filter(
+                        // if the explicitly set y_min and y_max are not compatible with the skipped range, it doesn't skip
+                        |(from, to)| y_min.lt_rat(from) && to.lt_rat(&y_max)
+                    ).collect()
+                }
+
+                else {
+                    vec![]
+                }
+            },
+            (SkipValue::Manual(ranges), _) => ranges.clone(),
+        };
+
+        skip_ranges.retain(|(from, to)| !from.lt_rat(&y_min) && !to.gt_rat(&y_max));
+
+        // each subplot needs at least a row
+        if self.plot_height < skip_ranges.len() * 2 + 1 {
+            skip_ranges.clear();
         }
 
+        if skip_ranges.is_empty() {
+            let (y_min, y_max) = prettify_y_labels(
+                &y_min,
+                &y_max,
+                self.plot_height,
+                self.pretty_y.as_ref().map(|n| (self.y_min.is_none(), self.y_max.is_none(), n.clone()))
+            );
+
+            return vec![(self.plot_height, y_min, y_max)];
+        }
+
+        let values = stats.map(|stats| &stats.values[..]).unwrap_or(&[]);
+        let heights = get_subplot_heights(values, &y_min, &skip_ranges, &y_max, self.plot_height, policy);
+        let mut result = Vec::with_capacity(heights.len());
+
+        for (index, height) in heights.into_iter().enumerate() {
+            let (from, from_movable) = if index == 0 {
+                (&y_min, self.y_min.is_none())
+            } else {
+                (&skip_ranges[index - 1].1, self.skip_value.is_automatic())
+            };
+            let (to, to_movable) = if index == skip_ranges.len() {
+                (&y_max, self.y_max.is_none())
+            } else {
+                (&skip_ranges[index].0, self.skip_value.is_automatic())
+            };
+
+            // if y_min, y_max, or skip_value is explicitly set by the user, it never touches them
+            // otherwise it tries to adjust them for prettier y_labels
+            let (subplot_y_min, subplot_y_max) = prettify_y_labels(
+                from,
+                to,
+                height,
+                self.pretty_y.as_ref().map(|n| (from_movable, to_movable, n.clone()))
+            );
+
+            result.push((height, subplot_y_min, subplot_y_max));
+        }
+
+        result
+    }
+
+    // the width of the y labels of the given range, with the settings of this graph
+    pub(crate) fn get_y_labels_width(&self, y_min: &Ratio, y_max: &Ratio) -> usize {
+        draw_y_labels_1d_plot(
+            y_min,
+            y_max,
+            self.plot_height,
+            self.y_label_margin,
+            &self.y_label_formatter,
+        ).get_width()
     }
 
-    let mut padding1 = data[curr_max_diff_ind].1.sub_rat(&data[0].1).div_i32(16);
-    let mut padding2 = data[data.len() - 1].1.sub_rat(&data[curr_max_diff_ind + 1].1).div_i32(16);
+    fn adjust_all_labeled_intervals(&mut self) {
+        let plot_width = self.get_actual_plot_width();
+
+        // the intervals of 2-dimensional data are in the x coordinates, not the indices of the dots
+        let data_len = match &self.data {
+            GraphData::Data2D { .. } => plot_width,
+            _ => self.data.len(),
+        };
+
+        if self.data.is_empty() {
+            return;
+        }
+
+        // the columns of time series are decided by the timestamps, not the indices
+        if let GraphData::TimeSeries(series) = &self.data {
+            let mut intervals = std::mem::take(&mut self.labeled_intervals);
+
+            for i in intervals.iter_mut() {
+                i.adjust_coordinate_by(|index| self.get_time_series_column(series, index));
+            }
+
+            self.labeled_intervals = intervals;
+        }
 
-    if padding1.is_zero() {
-        padding1 = data[curr_max_diff_ind + 1].1.sub_rat(&data[curr_max_diff_ind].1).div_i32(16);
+        else {
+            self.labeled_intervals.iter_mut().for_each(
+                |i| i.adjust_coordinate(plot_width, data_len)
+            );
+        }
     }
 
-    if padding2.is_zero() {
-        padding2 = data[curr_max_diff_ind + 1].1.sub_rat(&data[curr_max_diff_ind].1).div_i32(16);
+    // The column of the `index`th point of `series`, at its timestamp. The points before the x axis
+    // (including the negative indices) are at the first column, and the ones after it are right after the last column.
+    fn get_time_series_column(&self, series: &Series<i64>, index: i32) -> usize {
+        let plot_width = self.get_actual_plot_width();
+        let time_range = self.get_time_range(series);
+
+        if index < 0 {
+            return 0;
+        }
+
+        if index as usize >= series.len() {
+            return plot_width;
+        }
+
+        let t = *series.get_key(index as usize);
+
+        match get_column(t, time_range, plot_width) {
+            Some(column) => column,
+            None if t < time_range.0 => 0,
+            None => plot_width,
+        }
     }
+}
 
-    let mut ratio_of_subgraphs = (3, 3);
-    let values_below_skip_range = data[0..(curr_max_diff_ind + 1)].iter().map(|(_, n)| n).collect::<HashSet<&Ratio>>();
-    let values_above_skip_range = data[(curr_max_diff_ind + 1)..].iter().map(|(_, n)| n).collect::<HashSet<&Ratio>>();
+// Statistics of the values that decide the y axis, which are needed to skip ranges.
+// They're cached in `Graph` until the data changes, so the values are sorted only once.
+#[derive(Clone)]
+struct DataStats {
+    // distinct values, sorted
+    values: Vec<Ratio>,
 
-    if values_below_skip_range.len() * 2 > values_above_skip_range.len() * 3 {
-        ratio_of_subgraphs = (4, 2);
+    // `gaps[i]` is `values[i + 1] - values[i]`
+    gaps: Vec<Ratio>,
+}
+
+impl DataStats {
+    fn new(data: &[(String, Ratio)]) -> Self {
+        let mut values = data.iter().map(|(_, n)| n).collect::<Vec<&Ratio>>();
+        values.sort_unstable();
+        values.dedup();
+
+        // only the distinct values are cloned
+        let values = values.into_iter().cloned().collect::<Vec<Ratio>>();
+        let gaps = values.windows(2).map(|pair| pair[1].sub_rat(&pair[0])).collect();
+
+        DataStats { values, gaps }
+    }
+
+    // the same as `get_min_max`
+    fn get_min_max(&self, height: usize) -> (Ratio, Ratio) {
+        match (self.values.first(), self.values.last()) {
+            (Some(min), Some(max)) => add_margin_to_min_max(min, max, height),
+            _ => (Ratio::zero(), Ratio::one()),
+        }
+    }
+
+    fn get_max_gap(&self) -> Ratio {
+        self.gaps.iter().max().cloned().unwrap_or(Ratio::zero())
+    }
+}
+
+// It returns (y_min, skip_ranges, y_max). The largest gap between the values is always skipped, and the next gaps are skipped
+// only if they're larger than `1 / policy.min_gap_ratio` of the range that's left after skipping the largest one.
+// It skips at most `max_ranges` ranges.
+fn get_where_to_skip(stats: &DataStats, max_ranges: usize, policy: &SkipPolicy) -> (Ratio, Vec<(Ratio, Ratio)>, Ratio) {
+    let values = &stats.values;
+
+    if values.len() < 2 || max_ranges == 0 {
+        let (y_min, y_max) = (values[0].clone(), values[values.len() - 1].clone());
+        return (y_min, vec![], y_max);
+    }
+
+    // (gap, index): the gap between `values[index]` and `values[index + 1]`
+    let mut gaps = stats.gaps.iter().enumerate().map(|(i, gap)| (gap, i)).collect::<Vec<_>>();
+
+    // the earlier gap wins a tie
+    gaps.sort_by(|(g1, i1), (g2, i2)| g2.cmp(g1).then(i1.cmp(i2)));
+
+    let mut gaps = gaps.into_iter();
+
+    // the largest gap
+    let (gap, index) = gaps.next().unwrap();
+
+    // the range that's left after skipping the largest gap
+    let visible_range = values[values.len() - 1].sub_rat(&values[0]).sub_rat(gap);
+    let mut skipped_gaps = vec![(gap, index)];
+
+    for (gap, index) in gaps.take(max_ranges - 1) {
+        if !gap.mul_rat(&policy.min_gap_ratio).gt_rat(&visible_range) {
+            break;
+        }
+
+        skipped_gaps.push((gap, index));
     }
 
-    else if values_above_skip_range.len() * 2 > values_below_skip_range.len() * 3 {
-        ratio_of_subgraphs = (2, 4);
+    skipped_gaps.sort_by_key(|(_, index)| *index);
+
+    // (start, end) of each segment, both inclusive
+    let mut segments = Vec::with_capacity(skipped_gaps.len() + 1);
+    let mut start = 0;
+
+    for (_, index) in skipped_gaps.iter() {
+        segments.push((start, *index));
+        start = index + 1;
     }
 
+    segments.push((start, values.len() - 1));
+
+    let paddings = segments.iter().enumerate().map(
+        |(i, (start, end))| {
+            let padding = values[*end].sub_rat(&values[*start]).mul_rat(&policy.padding);
+
+            if padding.is_zero() {
+                // the smaller one of the adjacent skipped gaps
+                let below = if i > 0 { Some(skipped_gaps[i - 1].0) } else { None };
+                let above = skipped_gaps.get(i).map(|(gap, _)| *gap);
+
+                match (below, above) {
+                    (Some(below), Some(above)) => if below.lt_rat(above) { below } else { above },
+                    (Some(gap), None) | (None, Some(gap)) => gap,
+                    (None, None) => unreachable!(),
+                }.mul_rat(&policy.padding)
+            }
+
+            else {
+                padding
+            }
+        }
+    ).collect::<Vec<Ratio>>();
+
+    let skip_ranges = (0..skipped_gaps.len()).map(
+        |i| (
+            values[segments[i].1].add_rat(&paddings[i]),
+            values[segments[i + 1].0].sub_rat(&paddings[i + 1]),
+        )
+    ).filter(
+        |(from, to)| from.lt_rat(to)
+    ).collect();
+
     (
-        data[0].1.sub_rat(&padding1),
-        data[curr_max_diff_ind].1.add_rat(&padding1),
-        data[curr_max_diff_ind + 1].1.sub_rat(&padding2),
-        data[data.len() - 1].1.add_rat(&padding2),
-        ratio_of_subgraphs,
+        values[0].sub_rat(&paddings[0]),
+        skip_ranges,
+        values[values.len() - 1].add_rat(&paddings[paddings.len() - 1]),
     )
 }
 
+// The heights of the subplots, from the bottom to the top. `skip_ranges` are sorted and not empty.
+// Each subplot gets at least `policy.min_subplot_height` rows (if possible), and the remaining rows are
+// distributed by `policy.height_allocation`. `values` are the distinct values of the data.
+fn get_subplot_heights(
+    values: &[Ratio],
+    y_min: &Ratio,
+    skip_ranges: &[(Ratio, Ratio)],
+    y_max: &Ratio,
+    plot_height: usize,
+    policy: &SkipPolicy,
+) -> Vec<usize> {
+    let subplots = skip_ranges.len() + 1;
+
+    // a row for each horizontal line
+    let available_height = plot_height - skip_ranges.len();
+    let min_height = if available_height >= subplots * policy.min_subplot_height { policy.min_subplot_height } else { 1 };
+
+    let mut weights = match policy.height_allocation {
+        HeightAllocation::Equal => vec![Ratio::one(); subplots],
+        HeightAllocation::Density => {
+            let mut counts = vec![0; subplots];
+
+            for n in values.iter() {
+                let index = skip_ranges.iter().take_while(|(_, to)| n.geq_rat(to)).count();
+
+                // values inside the skipped ranges are not drawn
+                if index == skip_ranges.len() || n.leq_rat(&skip_ranges[index].0) {
+                    counts[index] += 1;
+                }
+            }
+
+            counts.into_iter().map(|count| Ratio::from_i64(count as i64)).collect()
+        },
+        HeightAllocation::Range => (0..subplots).map(
+            |index| {
+                let from = if index == 0 { y_min } else { &skip_ranges[index - 1].1 };
+                let to = if index == skip_ranges.len() { y_max } else { &skip_ranges[index].0 };
+
+                to.sub_rat(from)
+            }
+        ).collect(),
+    };
+
+    if weights.iter().all(|weight| weight.is_zero()) {
+        weights = vec![Ratio::one(); subplots];
+    }
+
+    let mut total_weight = Ratio::zero();
+
+    for weight in weights.iter() {
+        total_weight.add_rat_mut(weight);
+    }
+
+    let rest = available_height - min_height * subplots;
+    let mut heights = Vec::with_capacity(subplots);
+    let mut remainders = Vec::with_capacity(subplots);
+
+    for (index, weight) in weights.iter().enumerate() {
+        let height = weight.mul_i32(rest as i32).div_rat(&total_weight);
+        let truncated = height.truncate();
+
+        heights.push(min_height + truncated.truncate_bi().to_i32().unwrap_or(0) as usize);
+        remainders.push((height.sub_rat(&truncated), index));
+    }
+
+    // largest remainder method, the lower subplot wins a tie
+    remainders.sort_by(|(r1, i1), (r2, i2)| r2.cmp(r1).then(i1.cmp(i2)));
+
+    for (_, index) in remainders.into_iter().take(available_height - heights.iter().sum::<usize>()) {
+        heights[index] += 1;
+    }
+
+    heights
+}
+
 fn unwrap_y_min_max(self_y_min: &Option<Ratio>, self_y_max: &Option<Ratio>, data_min: &Ratio, data_max: &Ratio) -> (Ratio, Ratio) {
     match (&self_y_min, &self_y_max) {
         (Some(n), Some(m)) => (n.clone(), m.clone()),
-        (Some(n), None) => if n.lt_rat(&data_max) {
+        (Some(n), None) => if n.lt_rat(data_max) {
             (n.clone(), data_max.clone())
         } else {
             (n.clone(), n.add_i32(1))
         },
-        (None, Some(n)) => if n.gt_rat(&data_min) {
+        (None, Some(n)) => if n.gt_rat(data_min) {
             (data_min.clone(), n.clone())
         } else {
             (n.sub_i32(1), n.clone())
@@ -600,41 +1508,52 @@ fn unwrap_y_min_max(self_y_min: &Option<Ratio>, self_y_max: &Option<Ratio>, data
     }
 }
 
-fn get_min_max_diff(v: &Vec<(String, Ratio)>, height: usize) -> (Ratio, Ratio, Ratio) {  // (y_min, y_max, max_diff)
-    if v.len() == 0 {
-        return (Ratio::zero(), Ratio::one(), Ratio::zero());
-    }
-
-    let mut data = v.iter().map(|(_, n)| n.clone()).collect::<Vec<Ratio>>();
-    data.sort_unstable();
+// (y_min, y_max) of the data with a little margin, in a single pass
+fn get_min_max(data: &[(String, Ratio)], height: usize) -> (Ratio, Ratio) {
+    let Some((_, first)) = data.first() else {
+        return (Ratio::zero(), Ratio::one());
+    };
 
-    let curr_min = &data[0];
-    let curr_max = &data[data.len() - 1];
-    let mut max_diff = Ratio::zero();
+    let mut min = first;
+    let mut max = first;
 
-    for i in 0..(data.len() - 1) {
-        let diff = data[i + 1].sub_rat(&data[i]);
+    for (_, n) in data.iter() {
+        if n.lt_rat(min) {
+            min = n;
+        }
 
-        if diff.gt_rat(&max_diff) {
-            max_diff = diff;
+        if n.gt_rat(max) {
+            max = n;
         }
     }
 
+    add_margin_to_min_max(min, max, height)
+}
+
+// the y range of the data, with a little margin above and below
+fn add_margin_to_min_max(curr_min: &Ratio, curr_max: &Ratio, height: usize) -> (Ratio, Ratio) {
     let mut diff = curr_max.sub_rat(curr_min).div_i32(16);
 
     if diff.is_zero() {
         diff = Ratio::from_i32(height as i32).div_i32(4);
     }
 
-    let min = curr_min.sub_rat(&diff);
-    let max = curr_max.add_rat(&diff);
-
-    (min, max, max_diff)
+    (curr_min.sub_rat(&diff), curr_max.add_rat(&diff))
 }
 
-fn draw_title(title: &str, big_title: bool, title_color: Option<Color>) -> Lines {
+pub(crate) fn draw_title(title: &str, big_title: bool, title_color: Option<Color>, glyphs: &Glyphs) -> Lines {
     let mut result = if big_title {
-        Lines::from_string(&asciibox::render_string(title, asciibox::RenderOption::default()), Alignment::First, &ColorMode::None)
+        let mut result = Lines::from_string(&asciibox::render_string(title, asciibox::RenderOption::default()), Alignment::First, &ColorMode::None);
+
+        for y in 0..result.get_height() {
+            for x in 0..result.get_width() {
+                if let Grapheme::Char(c) = result.get(x, y) {
+                    result.set(x, y, glyphs.translate_quadrant(*c));
+                }
+            }
+        }
+
+        result
     }
 
     else {
@@ -647,7 +1566,7 @@ fn draw_title(title: &str, big_title: bool, title_color: Option<Color>) -> Lines
 }
 
 // no axis
-fn draw_y_labels_2d_plot(y_labels: &Vec<Option<String>>) -> Lines {
+fn draw_y_labels_2d_plot(y_labels: &[Option<String>]) -> Lines {
     Lines::from_string(
         &y_labels.iter().map(
             |s| match s {
@@ -660,6 +1579,20 @@ fn draw_y_labels_2d_plot(y_labels: &Vec<Option<String>>) -> Lines {
     )
 }
 
+// no axis
+fn draw_x_labels_2d_plot(x_labels: &[Option<String>], width: usize, margin: usize) -> Lines {
+    draw_x_labels(
+        &x_labels.iter().map(
+            |s| (
+                match s { Some(s) => s.to_string(), _ => String::new() },
+                ()
+            )
+        ).collect::<Vec<_>>(),
+        width,
+        margin,
+    ).0
+}
+
 // no axis
 fn draw_y_labels_1d_plot(
     y_min: &Ratio,
@@ -693,8 +1626,10 @@ fn draw_y_labels_1d_plot(
 }
 
 // no axis
-fn draw_x_labels<T>(data: &Vec<(String, T)>, width: usize, margin: usize) -> Lines {
+// (labels, the columns where the labels on the first line start)
+fn draw_x_labels<T>(data: &[(String, T)], width: usize, margin: usize) -> (Lines, Vec<usize>) {
     let mut result = Lines::new(width, 2);
+    let mut columns = vec![];
 
     let mut first_line_filled = 0;
     let mut second_line_filled = 0;
@@ -708,47 +1643,93 @@ fn draw_x_labels<T>(data: &Vec<(String, T)>, width: usize, margin: usize) -> Lin
             continue;
         }
 
-        let curr_label = &data[data_ind].0;
+        let curr_label = data[data_ind].0.replace('\n', " ");
+        let curr_label_width = display_width(&curr_label);
         let y_ind = on_first_line as usize;
 
-        if curr_label.len() + x >= width {
+        if curr_label_width + x >= width {
             on_first_line = !on_first_line;
             continue;
         }
 
-        for (lab_ind, c) in curr_label.chars().enumerate() {
-            let c = if c == '\n' { ' ' as u16 } else { c as u16 };
+        result.blit_mut(
+            &Lines::from_string(&curr_label, Alignment::First, &ColorMode::None),
+            x,
+            y_ind,
+            None,
+        );
 
-            result.set(x + lab_ind, y_ind, c);
+        if y_ind == 0 {
+            columns.push(x);
         }
 
         last_ind = data_ind;
 
         if on_first_line {
-            first_line_filled = x + curr_label.len() + margin;
+            first_line_filled = x + curr_label_width + margin;
         }
 
         else {
-            second_line_filled = x + curr_label.len() + margin;
+            second_line_filled = x + curr_label_width + margin;
         }
 
         on_first_line = !on_first_line;
     }
 
-    result
+    (result, columns)
+}
+
+// no axis
+// like `draw_x_labels`, but the labels are at the given columns
+fn draw_x_ticks(ticks: &[(usize, String)], width: usize, margin: usize) -> (Lines, Vec<usize>) {
+    let mut result = Lines::new(width, 2);
+    let mut columns = vec![];
+    let mut filled = [0, 0];
+    let mut on_first_line = false;
+
+    for (x, label) in ticks.iter() {
+        let label_width = display_width(label);
+
+        if label_width + x >= width {
+            continue;
+        }
+
+        let y_ind = if x >= &filled[on_first_line as usize] {
+            on_first_line as usize
+        } else if x >= &filled[!on_first_line as usize] {
+            !on_first_line as usize
+        } else {
+            continue;
+        };
+
+        result.blit_mut(
+            &Lines::from_string(label, Alignment::First, &ColorMode::None),
+            *x,
+            y_ind,
+            None,
+        );
+        if y_ind == 0 {
+            columns.push(*x);
+        }
+
+        filled[y_ind] = x + label_width + margin;
+        on_first_line = y_ind == 0;
+    }
+
+    (result, columns)
 }
 
 // no axis, no labels, only plots
-fn plot_2d(data: &Vec<(usize, usize, u16)>, width: usize, height: usize) -> Lines {
+fn plot_2d(data: &[(usize, usize, char)], width: usize, height: usize, glyphs: &Glyphs) -> Lines {
     let mut result = Lines::new(width, height);
 
     for (x, y, c) in data.iter() {
-        if *c == '\n' as u16 {
-            result.set(*x, *y, 32);
+        if *c == '\n' {
+            result.set(*x, *y, ' ');
         }
 
         else {
-            result.set(*x, *y, *c);
+            result.set(*x, *y, glyphs.translate_quadrant(*c));
         }
     }
 
@@ -756,9 +1737,57 @@ fn plot_2d(data: &Vec<(usize, usize, u16)>, width: usize, height: usize) -> Line
 }
 
 // no axis, no labels, only plots
-fn plot_1d(data: &Vec<(String, Ratio)>, width: usize, height: usize, y_min: &Ratio, y_max: &Ratio, no_overflow_char: bool, overflow_char_color: Option<Color>) -> Lines {
+// each cell is drawn with one of `levels`, which are (glyph, color) from the lowest to the highest
+fn plot_heatmap(data: &[Vec<Ratio>], y_min: &Ratio, y_max: &Ratio, levels: &[(char, Option<Color>)]) -> Lines {
+    let mut result = Lines::new(data[0].len(), data.len());
+
+    for (y, row) in data.iter().enumerate() {
+        for (x, n) in row.iter().enumerate() {
+            let (glyph, color) = &levels[heatmap_level(n, y_min, y_max, levels.len())];
+            result.set(x, y, *glyph);
+            result.set_color(x, y, color.clone());
+        }
+    }
+
+    result
+}
+
+// truncate((n - y_min) / (y_max - y_min) * levels), clamped to 0..levels
+fn heatmap_level(n: &Ratio, y_min: &Ratio, y_max: &Ratio, levels: usize) -> usize {
+    let y_diff = y_max.sub_rat(y_min);
+
+    if y_diff.is_zero() || n.leq_rat(y_min) {
+        0
+    }
+
+    else if n.geq_rat(y_max) {
+        levels - 1
+    }
+
+    else {
+        n.sub_rat(y_min).div_rat(&y_diff).mul_i32(levels as i32).truncate_bi().to_i32().unwrap_or(0).clamp(0, levels as i32 - 1) as usize
+    }
+}
+
+// the highest level comes first
+fn get_heatmap_scale(y_min: &Ratio, y_max: &Ratio, levels: &[(char, Option<Color>)], formatter: &Arc<dyn NumberFormatter>) -> Vec<LegendEntry> {
+    let y_step = y_max.sub_rat(y_min).div_i32(levels.len() as i32);
+
+    levels.iter().enumerate().rev().map(
+        |(index, (glyph, color))| {
+            let from = y_min.add_rat(&y_step.mul_i32(index as i32));
+            let to = y_min.add_rat(&y_step.mul_i32(index as i32 + 1));
+
+            LegendEntry { glyph: *glyph, color: color.clone(), label: format!("{} ~ {}", formatter.f(&from), formatter.f(&to)) }
+        }
+    ).collect()
+}
+
+// no axis, no labels, only plots
+#[allow(clippy::too_many_arguments)]
+fn plot_1d(data: &[(String, Ratio)], width: usize, height: usize, y_min: &Ratio, y_max: &Ratio, no_overflow_char: bool, overflow_char_color: Option<Color>, glyphs: &Glyphs) -> Lines {
     let mut result = Lines::new(width, height);
-    let y_diff = y_max.sub_rat(&y_min);
+    let y_diff = y_max.sub_rat(y_min);
 
     for x in 0..width {
         let data_ind = x * data.len() / width;
@@ -783,21 +1812,16 @@ fn plot_1d(data: &Vec<(String, Ratio)>, width: usize, height: usize, y_min: &Rat
         }
 
         for y in y_start..height {
-            result.set(x, y, '█' as u16);
+            result.set(x, y, glyphs.bars[0]);
         }
 
         if overflow && !no_overflow_char {
-            result.set(x, 0, '^' as u16);
+            result.set(x, 0, glyphs.overflow);
             result.set_color(x, 0, overflow_char_color.clone());
         }
 
         else {
-            result.set(x, y_start, [
-                '█' as u16,
-                '▆' as u16,
-                '▄' as u16,
-                '▂' as u16,
-            ][block_type])
+            result.set(x, y_start, glyphs.bars[block_type])
         }
 
     }
@@ -855,22 +1879,88 @@ fn prettify_y_labels(old_y_min: &Ratio, old_y_max: &Ratio, height: usize, pretty
     }
 }
 
-fn draw_vertial_line(height: usize, color: Option<Color>) -> Lines {
-    let mut result = Lines::new(2, height);
-    result.set_color_all(color);
+// The columns of each data, like `plot_1d`, which draws `data[x * data_len / width]` at the column `x`.
+// If the data is longer than the plot, a column is shared by many data.
+fn get_data_columns(data_len: usize, width: usize) -> Vec<Range<usize>> {
+    (0..data_len).map(
+        |index| if data_len <= width {
+            (index * width).div_ceil(data_len)..((index + 1) * width).div_ceil(data_len)
+        } else {
+            let x = ((index + 1) * width).div_ceil(data_len) - 1;
 
-    for i in 0..height {
-        if i & 1 == 0 {
-            result.set(0, i, ')' as u16);
-            result.set(1, i, ')' as u16);
+            x..(x + 1)
+        }
+    ).collect()
+}
+
+// `offset` is the number of columns on the left side of the plot (y labels and the y axis)
+fn apply_horizontal_breaks(plot: &Lines, offset: usize, breaks: &[(usize, usize)], color: Option<Color>, glyphs: &Glyphs) -> Lines {
+    let height = plot.get_height();
+    let line = draw_vertial_line(height, color, glyphs);
+
+    // (from, to) of the visible columns of `plot`
+    let mut segments = Vec::with_capacity(breaks.len() + 1);
+    segments.push((0, offset + breaks[0].0));
+
+    for (index, (_, to)) in breaks.iter().enumerate() {
+        let next_from = match breaks.get(index + 1) {
+            Some((from, _)) => offset + from,
+            None => plot.get_width(),
+        };
+
+        segments.push((offset + to, next_from));
+    }
+
+    let width = segments.iter().map(|(from, to)| to - from).sum::<usize>() + line.get_width() * breaks.len();
+    let mut result = Lines::new(width, height);
+    let mut x = 0;
+
+    for (index, (from, to)) in segments.into_iter().enumerate() {
+        if index > 0 {
+            result.blit_mut(&line, x, 0, None);
+            x += line.get_width();
+        }
+
+        result.blit_region_mut(plot, (from, 0, to - from, height), x, 0, None);
+        x += to - from;
+    }
+
+    result
+}
+
+// (the column after the breaks are applied, whether it's visible)
+// If it's hidden, it's the column of the break line. Each break line is 2 characters wide.
+fn apply_breaks_to_column(x: usize, breaks: &[(usize, usize)]) -> (usize, bool) {
+    // it can be negative when a break is narrower than its line
+    let mut shift = 0i64;
+
+    for (from, to) in breaks.iter() {
+        if x >= *to {
+            shift += 2 - (to - from) as i64;
+        }
+
+        else if x >= *from {
+            return ((*from as i64 + shift) as usize, false);
         }
 
         else {
-            result.set(0, i, '(' as u16);
-            result.set(1, i, '(' as u16);
+            break;
         }
     }
 
+    ((x as i64 + shift) as usize, true)
+}
+
+fn draw_vertial_line(height: usize, color: Option<Color>, glyphs: &Glyphs) -> Lines {
+    let mut result = Lines::new(2, height);
+    result.set_color_all(color);
+
+    for i in 0..height {
+        let c = glyphs.horizontal_break[i & 1];
+        result.set(0, i, c);
+        result.set(1, i, c);
+    }
+
     result
 }
 
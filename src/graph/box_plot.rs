@@ -0,0 +1,381 @@
+use crate::charset::Glyphs;
+use crate::color::Color;
+use crate::lines::Lines;
+use hmath::Ratio;
+use std::ops::Range;
+
+/// A box-and-whisker summary of samples. Whiskers are at the furthest samples within 1.5 IQR from the box,
+/// and the samples beyond the whiskers are outliers.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct BoxStats {
+    pub lower_whisker: Ratio,
+    pub q1: Ratio,
+    pub median: Ratio,
+    pub q3: Ratio,
+    pub upper_whisker: Ratio,
+    pub outliers: Vec<Ratio>,
+}
+
+impl BoxStats {
+    /// It returns `None` if `samples` is empty.
+    pub fn new(samples: &[Ratio]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut samples = samples.to_vec();
+        samples.sort_unstable();
+
+        let q1 = quantile(&samples, 1, 4);
+        let median = quantile(&samples, 2, 4);
+        let q3 = quantile(&samples, 3, 4);
+
+        // 1.5 * IQR
+        let fence = q3.sub_rat(&q1).mul_i32(3).div_i32(2);
+        let lower_fence = q1.sub_rat(&fence);
+        let upper_fence = q3.add_rat(&fence);
+
+        let mut outliers = vec![];
+        let mut lower_whisker = None;
+        let mut upper_whisker = None;
+
+        for n in samples.into_iter() {
+            if n.lt_rat(&lower_fence) || n.gt_rat(&upper_fence) {
+                outliers.push(n);
+            }
+
+            else {
+                if lower_whisker.is_none() {
+                    lower_whisker = Some(n.clone());
+                }
+
+                upper_whisker = Some(n);
+            }
+        }
+
+        Some(BoxStats {
+            // there's at least one sample between the fences: the median
+            lower_whisker: lower_whisker.unwrap(),
+            q1,
+            median,
+            q3,
+            upper_whisker: upper_whisker.unwrap(),
+            outliers,
+        })
+    }
+
+    // the values that have to be visible in the plot
+    pub fn values(&self) -> Vec<Ratio> {
+        let mut result = Vec::with_capacity(self.outliers.len() + 5);
+        result.extend_from_slice(&[
+            self.lower_whisker.clone(),
+            self.q1.clone(),
+            self.median.clone(),
+            self.q3.clone(),
+            self.upper_whisker.clone(),
+        ]);
+        result.extend_from_slice(&self.outliers);
+
+        result
+    }
+}
+
+// `sorted` is not empty
+// linear interpolation between the closest ranks: `sorted[(len - 1) * numer / denom]`
+fn quantile(sorted: &[Ratio], numer: usize, denom: usize) -> Ratio {
+    let position = (sorted.len() - 1) * numer;
+    let (index, rem) = (position / denom, position % denom);
+
+    if rem == 0 {
+        sorted[index].clone()
+    }
+
+    else {
+        sorted[index].add_rat(&sorted[index + 1].sub_rat(&sorted[index]).mul_i32(rem as i32).div_i32(denom as i32))
+    }
+}
+
+// The columns (or rows) of the `index`th category. It's empty if there're more categories than `length`.
+pub(crate) fn category_range(index: usize, categories: usize, length: usize) -> Range<usize> {
+    (index * length).div_ceil(categories)..((index + 1) * length).div_ceil(categories)
+}
+
+// If there's enough room, it leaves a margin on both sides, so that adjacent boxes don't touch each other.
+fn inner_range(range: Range<usize>) -> Range<usize> {
+    if range.len() >= 4 {
+        (range.start + 1)..(range.end - 1)
+    }
+
+    else {
+        range
+    }
+}
+
+// floor((n - from) / (to - from) * length). `to` is at `length - 1`, not `length`.
+// If `n` is out of the range, it's -1 or `length`.
+fn to_cell(n: &Ratio, from: &Ratio, to: &Ratio, length: usize) -> i64 {
+    if n.lt_rat(from) {
+        -1
+    }
+
+    else if n.gt_rat(to) {
+        length as i64
+    }
+
+    else if n.eq_rat(to) {
+        length as i64 - 1
+    }
+
+    else {
+        n.sub_rat(from).div_rat(&to.sub_rat(from)).mul_i32(length as i32).floor_bi().to_i64().unwrap_or(0)
+    }
+}
+
+// cells from `from` to `to` (both inclusive), clipped by `0..length`
+fn cells_between(from: i64, to: i64, length: usize) -> Range<usize> {
+    let (from, to) = (from.min(to), from.max(to));
+
+    (from.max(0) as usize)..((to + 1).clamp(0, length as i64) as usize)
+}
+
+fn is_visible(cell: i64, length: usize) -> bool {
+    0 <= cell && cell < length as i64
+}
+
+// no axis, no labels, only plots
+// The y axis goes from `y_max` (top) to `y_min` (bottom), and the values outside the range are clipped.
+pub(crate) fn plot_boxes_vertical(
+    data: &[(String, Option<BoxStats>)],
+    width: usize,
+    height: usize,
+    y_min: &Ratio,
+    y_max: &Ratio,
+    color: Option<Color>,
+    glyphs: &Glyphs,
+) -> Lines {
+    let mut result = Lines::new(width, height);
+
+    // the row 0 is the top
+    let row = |n: &Ratio| height as i64 - 1 - to_cell(n, y_min, y_max, height);
+
+    for (index, (_, stats)) in data.iter().enumerate() {
+        let stats = match stats {
+            Some(stats) => stats,
+            None => { continue; },
+        };
+        let columns = inner_range(category_range(index, data.len(), width));
+
+        if columns.is_empty() {
+            continue;
+        }
+
+        let center = (columns.start + columns.end - 1) / 2;
+        let (top, median, bottom) = (row(&stats.q3), row(&stats.median), row(&stats.q1));
+        let (upper_whisker, lower_whisker) = (row(&stats.upper_whisker), row(&stats.lower_whisker));
+
+        for y in cells_between(upper_whisker, lower_whisker, height) {
+            result.set(center, y, glyphs.vertical);
+        }
+
+        for (whisker, tee) in [(upper_whisker, glyphs.tees[2]), (lower_whisker, glyphs.tees[3])] {
+            if is_visible(whisker, height) {
+                for x in columns.clone() {
+                    result.set(x, whisker as usize, glyphs.horizontal);
+                }
+
+                result.set(center, whisker as usize, tee);
+            }
+        }
+
+        for y in cells_between(top, bottom, height) {
+            let y_ = y as i64;
+
+            for x in columns.clone() {
+                let c = if columns.len() < 3 {
+                    if y_ == median { glyphs.horizontal } else { glyphs.bars[0] }
+                }
+
+                else {
+                    let (left, right) = (x == columns.start, x + 1 == columns.end);
+
+                    match (left, right) {
+                        (true, _) if y_ == median => glyphs.tees[0],
+                        (_, true) if y_ == median => glyphs.tees[1],
+                        (true, _) if y_ == top => glyphs.corners[0],
+                        (_, true) if y_ == top => glyphs.corners[1],
+                        (true, _) if y_ == bottom => glyphs.corners[2],
+                        (_, true) if y_ == bottom => glyphs.corners[3],
+                        (true, _) | (_, true) => glyphs.vertical,
+                        _ if y_ == median || y_ == top || y_ == bottom => glyphs.horizontal,
+                        _ => ' ',
+                    }
+                };
+
+                result.set(x, y, c);
+            }
+        }
+
+        for outlier in stats.outliers.iter() {
+            let y = row(outlier);
+
+            if is_visible(y, height) {
+                result.set(center, y as usize, glyphs.outlier);
+            }
+        }
+
+        for x in columns.clone() {
+            for y in 0..height {
+                result.set_color(x, y, color.clone());
+            }
+        }
+    }
+
+    result
+}
+
+// no axis, no labels, only plots
+// The x axis goes from `x_min` (left) to `x_max` (right), and the values outside the range are clipped.
+pub(crate) fn plot_boxes_horizontal(
+    data: &[(String, Option<BoxStats>)],
+    width: usize,
+    height: usize,
+    x_min: &Ratio,
+    x_max: &Ratio,
+    color: Option<Color>,
+    glyphs: &Glyphs,
+) -> Lines {
+    let mut result = Lines::new(width, height);
+    let column = |n: &Ratio| to_cell(n, x_min, x_max, width);
+
+    for (index, (_, stats)) in data.iter().enumerate() {
+        let stats = match stats {
+            Some(stats) => stats,
+            None => { continue; },
+        };
+        let rows = inner_range(category_range(index, data.len(), height));
+
+        if rows.is_empty() {
+            continue;
+        }
+
+        let center = (rows.start + rows.end - 1) / 2;
+        let (left, median, right) = (column(&stats.q1), column(&stats.median), column(&stats.q3));
+        let (lower_whisker, upper_whisker) = (column(&stats.lower_whisker), column(&stats.upper_whisker));
+
+        for x in cells_between(lower_whisker, upper_whisker, width) {
+            result.set(x, center, glyphs.horizontal);
+        }
+
+        for (whisker, tee) in [(lower_whisker, glyphs.tees[0]), (upper_whisker, glyphs.tees[1])] {
+            if is_visible(whisker, width) {
+                for y in rows.clone() {
+                    result.set(whisker as usize, y, glyphs.vertical);
+                }
+
+                result.set(whisker as usize, center, tee);
+            }
+        }
+
+        for x in cells_between(left, right, width) {
+            let x_ = x as i64;
+
+            for y in rows.clone() {
+                let c = if rows.len() < 3 {
+                    if x_ == median { glyphs.vertical } else { glyphs.bars[0] }
+                }
+
+                else {
+                    let (top, bottom) = (y == rows.start, y + 1 == rows.end);
+
+                    match (top, bottom) {
+                        (true, _) if x_ == median => glyphs.tees[2],
+                        (_, true) if x_ == median => glyphs.tees[3],
+                        (true, _) if x_ == left => glyphs.corners[0],
+                        (true, _) if x_ == right => glyphs.corners[1],
+                        (_, true) if x_ == left => glyphs.corners[2],
+                        (_, true) if x_ == right => glyphs.corners[3],
+                        (true, _) | (_, true) => glyphs.horizontal,
+                        _ if x_ == median || x_ == left || x_ == right => glyphs.vertical,
+                        _ => ' ',
+                    }
+                };
+
+                result.set(x, y, c);
+            }
+        }
+
+        for outlier in stats.outliers.iter() {
+            let x = column(outlier);
+
+            if is_visible(x, width) {
+                result.set(x as usize, center, glyphs.outlier);
+            }
+        }
+
+        for y in rows.clone() {
+            for x in 0..width {
+                result.set_color(x, y, color.clone());
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratios(samples: &[i32]) -> Vec<Ratio> {
+        samples.iter().map(|n| Ratio::from_i32(*n)).collect()
+    }
+
+    // numer / denom
+    fn frac(numer: i32, denom: i32) -> Ratio {
+        Ratio::from_i32(numer).div_i32(denom)
+    }
+
+    fn check(samples: &[i32], expected: [Ratio; 5], outliers: &[i32]) {
+        let stats = BoxStats::new(&ratios(samples)).unwrap();
+
+        assert_eq!(
+            [stats.lower_whisker, stats.q1, stats.median, stats.q3, stats.upper_whisker],
+            expected,
+            "{samples:?}",
+        );
+        assert_eq!(stats.outliers, ratios(outliers), "{samples:?}");
+    }
+
+    #[test]
+    fn empty_samples() {
+        assert_eq!(BoxStats::new(&[]), None);
+    }
+
+    #[test]
+    fn small_samples() {
+        check(&[5], [frac(5, 1), frac(5, 1), frac(5, 1), frac(5, 1), frac(5, 1)], &[]);
+
+        // q1 = 1 + (3 - 1) / 4, q3 = 1 + (3 - 1) * 3 / 4
+        check(&[3, 1], [frac(1, 1), frac(3, 2), frac(2, 1), frac(5, 2), frac(3, 1)], &[]);
+        check(&[4, 1, 3, 2], [frac(1, 1), frac(7, 4), frac(5, 2), frac(13, 4), frac(4, 1)], &[]);
+
+        // the fences are at 3 - 6 and 7 + 6
+        check(&[30, 1, 2, 3, 4, 5, 6, 7, 8], [frac(1, 1), frac(3, 1), frac(5, 1), frac(7, 1), frac(8, 1)], &[30]);
+        check(&[-10, 5, 5, 6, 7, 7, 8, 9, 25], [frac(5, 1), frac(5, 1), frac(7, 1), frac(8, 1), frac(9, 1)], &[-10, 25]);
+    }
+
+    #[test]
+    fn samples_on_the_fences() {
+        // the fences are at -3 and 13, and the samples on the fences are not outliers
+        check(&[-3, 2, 3, 4, 5, 6, 7, 8, 13], [frac(-3, 1), frac(3, 1), frac(5, 1), frac(7, 1), frac(13, 1)], &[]);
+        check(&[-4, 2, 3, 4, 5, 6, 7, 8, 14], [frac(2, 1), frac(3, 1), frac(5, 1), frac(7, 1), frac(8, 1)], &[-4, 14]);
+    }
+
+    #[test]
+    fn ties() {
+        // IQR is 0, so every sample that's not 5 is an outlier
+        check(&[5, 5, 5, 5, 20], [frac(5, 1), frac(5, 1), frac(5, 1), frac(5, 1), frac(5, 1)], &[20]);
+        check(&[1, 5, 5, 5, 5, 5, 5, 5, 5], [frac(5, 1), frac(5, 1), frac(5, 1), frac(5, 1), frac(5, 1)], &[1]);
+        check(&[2, 2, 2, 2], [frac(2, 1), frac(2, 1), frac(2, 1), frac(2, 1), frac(2, 1)], &[]);
+    }
+}
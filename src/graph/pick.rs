@@ -0,0 +1,85 @@
+use super::{Graph, GraphData, get_data_columns};
+use crate::graph::box_plot::category_range;
+use crate::layout::{Layout, Pick};
+use crate::orientation::Orientation;
+use hmath::Ratio;
+
+impl Graph {
+    /// It tells what's at (`x`, `y`) of the output, where (0, 0) is the top-left corner.
+    /// `layout` must be from `render_with_metadata` or `layout` of this graph, with the same settings.
+    ///
+    /// It returns `None` if (`x`, `y`) is not in the plot area, or if it's on a horizontal break.
+    pub fn pick(&self, layout: &Layout, x: usize, y: usize) -> Option<Pick> {
+        if !layout.plot_area.contains(x, y) {
+            return None;
+        }
+
+        let (column, row) = (x - layout.plot_area.x, y - layout.plot_area.y);
+
+        match &self.data {
+            GraphData::Data1D(_)
+            | GraphData::TimeSeries(_) => {
+                let index = layout.columns.iter().position(|columns| columns.contains(&x))?;
+                let drawn = self.get_y_axis_data()?;
+                let plot_width = self.get_actual_plot_width();
+
+                // the column before the horizontal breaks are applied
+                let column = get_data_columns(layout.columns.len(), plot_width)[index].start;
+                let (label, value) = &drawn[column * drawn.len() / plot_width];
+
+                Some(Pick::Data {
+                    index,
+                    label: label.clone(),
+                    value: Some(value.clone()),
+                    axis_value: get_y_axis_value(layout, y),
+                })
+            },
+            GraphData::BoxPlot(data) => {
+                let (index, axis_value) = match self.orientation {
+                    Orientation::Vertical => (
+                        layout.columns.iter().position(|columns| columns.contains(&x))?,
+                        get_y_axis_value(layout, y),
+                    ),
+                    Orientation::Horizontal => (
+                        (0..data.len()).position(|index| category_range(index, data.len(), layout.plot_area.height).contains(&row))?,
+                        layout.x_range.as_ref().map(
+                            |(x_min, x_max)| x_min.add_rat(&x_max.sub_rat(x_min).mul_i32(column as i32).div_i32(layout.plot_area.width as i32))
+                        ),
+                    ),
+                };
+                let (label, stats) = &data[index];
+
+                Some(Pick::Data {
+                    index,
+                    label: label.clone(),
+                    value: stats.as_ref().map(|stats| stats.median.clone()),
+                    axis_value,
+                })
+            },
+            GraphData::Data2D { x_labels, y_labels, .. } => Some(Pick::Cell {
+                x: column,
+                y: row,
+                x_label: x_labels.get(column).cloned().flatten(),
+                y_label: y_labels.get(row).cloned().flatten(),
+                value: None,
+            }),
+            GraphData::Heatmap { data, x_labels, y_labels } => Some(Pick::Cell {
+                x: column,
+                y: row,
+                x_label: x_labels.get(column).cloned().flatten(),
+                y_label: y_labels.get(row).cloned().flatten(),
+                value: data.get(row).and_then(|data| data.get(column)).cloned(),
+            }),
+            GraphData::None => None,
+        }
+    }
+}
+
+// `y_max - (y_max - y_min) / height * row`, like the y labels
+fn get_y_axis_value(layout: &Layout, y: usize) -> Option<Ratio> {
+    let y_scale = layout.y_scales.iter().find(|y_scale| y_scale.rows.contains(&y))?;
+    let height = y_scale.rows.len() as i32;
+    let row = (y - y_scale.rows.start) as i32;
+
+    Some(y_scale.y_max.sub_rat(&y_scale.y_max.sub_rat(&y_scale.y_min).div_i32(height).mul_i32(row)))
+}
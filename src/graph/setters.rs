@@ -1,8 +1,17 @@
 use crate::Graph;
+use crate::annotation::Annotation;
+use crate::charset::Charset;
 use crate::color::{Color, ColorMode};
+use crate::downsampling::Downsampling;
 use crate::format::{DefaultFormatter, NumberFormatter};
-use crate::graph::GraphData;
-use crate::interval::Interval;
+use crate::graph::{DataCache, GraphData};
+use crate::graph::box_plot::BoxStats;
+use crate::gridlines::Gridlines;
+use crate::interval::{Interval, IntervalHighlight};
+use crate::legend::Legend;
+use crate::numeric::{Series, sanitize_f64};
+use crate::orientation::Orientation;
+use crate::skip_policy::SkipPolicy;
 use crate::skip_value::SkipValue;
 use hmath::Ratio;
 use std::sync::Arc;
@@ -18,12 +27,13 @@ impl Graph {
 
         self.data = GraphData::Data2D {
             data: data.iter().map(|(x, y, c)| (
-                *x, *y, *c as u16
+                *x, *y, *c
             )).collect(),
             x_labels: x_labels.to_vec(),
             y_labels: y_labels.to_vec(),
         };
 
+        self.clear_cache();
         self.adjust_all_labeled_intervals();
 
         self
@@ -53,91 +63,91 @@ impl Graph {
                         true, true,
                         true, true,
                     ) => {
-                        data.push((x, y, '█' as u16));
+                        data.push((x, y, '█'));
                     },
                     (
                         true, true,
                         true, false,
                     ) => {
-                        data.push((x, y, '▛' as u16));
+                        data.push((x, y, '▛'));
                     },
                     (
                         true, true,
                         false, true,
                     ) => {
-                        data.push((x, y, '▜' as u16));
+                        data.push((x, y, '▜'));
                     },
                     (
                         true, true,
                         false, false,
                     ) => {
-                        data.push((x, y, '▀' as u16));
+                        data.push((x, y, '▀'));
                     },
                     (
                         true, false,
                         true, true,
                     ) => {
-                        data.push((x, y, '▙' as u16));
+                        data.push((x, y, '▙'));
                     },
                     (
                         true, false,
                         true, false,
                     ) => {
-                        data.push((x, y, '▌' as u16));
+                        data.push((x, y, '▌'));
                     },
                     (
                         true, false,
                         false, true,
                     ) => {
-                        data.push((x, y, '▚' as u16));
+                        data.push((x, y, '▚'));
                     },
                     (
                         true, false,
                         false, false,
                     ) => {
-                        data.push((x, y, '▘' as u16));
+                        data.push((x, y, '▘'));
                     },
                     (
                         false, true,
                         true, true,
                     ) => {
-                        data.push((x, y, '▟' as u16));
+                        data.push((x, y, '▟'));
                     },
                     (
                         false, true,
                         true, false,
                     ) => {
-                        data.push((x, y, '▞' as u16));
+                        data.push((x, y, '▞'));
                     },
                     (
                         false, true,
                         false, true,
                     ) => {
-                        data.push((x, y, '▐' as u16));
+                        data.push((x, y, '▐'));
                     },
                     (
                         false, true,
                         false, false,
                     ) => {
-                        data.push((x, y, '▝' as u16));
+                        data.push((x, y, '▝'));
                     },
                     (
                         false, false,
                         true, true,
                     ) => {
-                        data.push((x, y, '▄' as u16));
+                        data.push((x, y, '▄'));
                     },
                     (
                         false, false,
                         true, false,
                     ) => {
-                        data.push((x, y, '▖' as u16));
+                        data.push((x, y, '▖'));
                     },
                     (
                         false, false,
                         false, true,
                     ) => {
-                        data.push((x, y, '▗' as u16));
+                        data.push((x, y, '▗'));
                     },
                     (
                         false, false,
@@ -153,6 +163,31 @@ impl Graph {
             y_labels: y_labels.to_vec(),
         };
 
+        self.clear_cache();
+        self.adjust_all_labeled_intervals();
+
+        self
+    }
+
+    /// It draws a heatmap of a grid of numbers. `data[y][x]` is the value of a cell, and each cell is drawn with a character.
+    /// The sizes of `x_labels` and `y_labels` must match the width and the height of `data`.
+    /// If `self.plot_width` and `self.plot_height` are already set, it updates them.
+    ///
+    /// The range of the shades (or colors) is from the minimum to the maximum of `data`. You can override it with `set_y_range`.
+    /// `T` can be any number type, including f32 and f64. NaN is converted to 0, -Inf is converted to f32::MIN and Inf to f32::MAX (or f64).
+    pub fn set_heatmap_data<T: TryInto<Ratio> + Clone>(&mut self, data: &[Vec<T>], x_labels: &[Option<String>], y_labels: &[Option<String>]) -> &mut Self {
+        self.plot_width = x_labels.len();
+        self.plot_height = y_labels.len();
+
+        self.data = GraphData::Heatmap {
+            data: data.iter().map(
+                |row| row.iter().map(|n| n.clone().try_into().unwrap_or(Ratio::zero())).collect()
+            ).collect(),
+            x_labels: x_labels.to_vec(),
+            y_labels: y_labels.to_vec(),
+        };
+
+        self.clear_cache();
         self.adjust_all_labeled_intervals();
 
         self
@@ -165,7 +200,29 @@ impl Graph {
         // in order for `String`s to be `T`, it has to clone n inside the `map` method.
         let data: Vec<(String, Ratio)> = data.iter().enumerate().map(|(i, n)| (i.to_string(), n.clone().try_into().unwrap_or(Ratio::zero()))).collect();
 
+        let data = Series::Exact(data);
+
+        self.data_range = data.get_range();
+        self.data = GraphData::Data1D(data);
+        self.clear_cache();
+        self.adjust_all_labeled_intervals();
+
+        self
+    }
+
+    /// Like `set_1d_data`, but the values are stored as f64 and the per-point work (downsampling) uses f64 math instead of exact ratios.
+    /// It's much faster with long data (like 100k points), and the downsampled values may have rounding errors.
+    /// The y axis and its labels are still calculated exactly.
+    ///
+    /// NaN is converted to 0, -Inf is converted to f64::MIN and Inf to f64::MAX.
+    pub fn set_1d_data_f64(&mut self, data: &[f64]) -> &mut Self {
+        let data: Vec<(String, f64)> = data.iter().enumerate().map(|(i, n)| (i.to_string(), sanitize_f64(*n))).collect();
+
+        let data = Series::F64(data);
+
+        self.data_range = data.get_range();
         self.data = GraphData::Data1D(data);
+        self.clear_cache();
         self.adjust_all_labeled_intervals();
 
         self
@@ -177,7 +234,106 @@ impl Graph {
         // in order for `String`s to be `T`, it has to clone n inside the `map` method.
         let data: Vec<(String, Ratio)> = data.iter().map(|(label, n)| (label.to_string(), n.clone().try_into().unwrap_or(Ratio::zero()))).collect();
 
+        let data = Series::Exact(data);
+
+        self.data_range = data.get_range();
         self.data = GraphData::Data1D(data);
+        self.clear_cache();
+        self.adjust_all_labeled_intervals();
+
+        self
+    }
+
+    /// Like `set_1d_labeled_data`, but it uses f64 math. See `set_1d_data_f64`.
+    pub fn set_1d_labeled_data_f64(&mut self, data: &[(String, f64)]) -> &mut Self {
+        let data: Vec<(String, f64)> = data.iter().map(|(label, n)| (label.to_string(), sanitize_f64(*n))).collect();
+
+        let data = Series::F64(data);
+
+        self.data_range = data.get_range();
+        self.data = GraphData::Data1D(data);
+        self.clear_cache();
+        self.adjust_all_labeled_intervals();
+
+        self
+    }
+
+    // `data_range` is (min, max) of `data`, which the caller already knows. It's `None` if `data` is empty.
+    pub(crate) fn set_1d_data_with_range(&mut self, data: Vec<(String, Ratio)>, data_range: Option<(Ratio, Ratio)>) -> &mut Self {
+        self.data = GraphData::Data1D(Series::Exact(data));
+        self.data_range = data_range;
+        self.clear_cache();
+        self.adjust_all_labeled_intervals();
+
+        self
+    }
+
+    // It moves the data out of `self`, so that the caller can modify it without copying it, and give it back with `set_1d_data_with_range`.
+    // It's empty if the data is not 1-dimensional exact values.
+    pub(crate) fn take_1d_data(&mut self) -> Vec<(String, Ratio)> {
+        self.clear_cache();
+
+        match std::mem::replace(&mut self.data, GraphData::None) {
+            GraphData::Data1D(Series::Exact(data)) => data,
+            _ => vec![],
+        }
+    }
+
+    /// `data` is `Vec<(unix timestamp in seconds, value)>`. The points don't have to be evenly spaced or sorted.
+    /// They're resampled into the columns of the plot, and the x axis is labeled at round time boundaries (minute, hour, day, month...).
+    /// All the timestamps are in UTC. If you want another time zone, add the offset to the timestamps.
+    ///
+    /// `T` can be any number type, including f32 and f64. NaN is converted to 0, -Inf is converted to f32::MIN and Inf to f32::MAX (or f64).\
+    /// `block_width` is ignored.
+    pub fn set_time_series_data<T: TryInto<Ratio> + Clone>(&mut self, data: &[(i64, T)]) -> &mut Self {
+        let mut data: Vec<(i64, Ratio)> = data.iter().map(|(t, n)| (*t, n.clone().try_into().unwrap_or(Ratio::zero()))).collect();
+        data.sort_by_key(|(t, _)| *t);
+
+        self.data = GraphData::TimeSeries(Series::Exact(data));
+        self.clear_cache();
+        self.adjust_all_labeled_intervals();
+
+        self
+    }
+
+    /// Like `set_time_series_data`, but the values are stored as f64 and the resampling uses f64 math. See `set_1d_data_f64`.
+    pub fn set_time_series_data_f64(&mut self, data: &[(i64, f64)]) -> &mut Self {
+        let mut data: Vec<(i64, f64)> = data.iter().map(|(t, n)| (*t, sanitize_f64(*n))).collect();
+        data.sort_by_key(|(t, _)| *t);
+
+        self.data = GraphData::TimeSeries(Series::F64(data));
+        self.clear_cache();
+        self.adjust_all_labeled_intervals();
+
+        self
+    }
+
+    /// It draws a box plot. `data` is `Vec<(category, samples)>`, and the quartiles, whiskers and outliers
+    /// of each category are calculated from the samples. Whiskers are at the furthest samples within 1.5 IQR from the box.
+    /// A category without any samples is left blank.
+    ///
+    /// `T` can be any number type, including f32 and f64. NaN is converted to 0, -Inf is converted to f32::MIN and Inf to f32::MAX (or f64).\
+    /// See `set_orientation` to draw the boxes horizontally.
+    pub fn set_box_plot_data<T: TryInto<Ratio> + Clone>(&mut self, data: &[(String, Vec<T>)]) -> &mut Self {
+        let data = data.iter().map(
+            |(label, samples)| (
+                label.to_string(),
+                BoxStats::new(&samples.iter().map(|n| n.clone().try_into().unwrap_or(Ratio::zero())).collect::<Vec<_>>()),
+            )
+        ).collect();
+
+        self.data = GraphData::BoxPlot(data);
+        self.clear_cache();
+        self.adjust_all_labeled_intervals();
+
+        self
+    }
+
+    /// By default, the x axis of a time series goes from the first timestamp to the last one.
+    /// With this, you can set the range explicitly, for example, to align multiple time series. The points out of the range are not drawn.
+    pub fn set_time_range(&mut self, from: i64, to: i64) -> &mut Self {
+        self.time_range = Some((from, to));
+        self.clear_cache();
         self.adjust_all_labeled_intervals();
 
         self
@@ -213,6 +369,7 @@ impl Graph {
 
     pub fn set_plot_width(&mut self, plot_width: usize) -> &mut Self {
         self.plot_width = plot_width;
+        self.clear_cache();
         self.adjust_all_labeled_intervals();
 
         self
@@ -227,6 +384,9 @@ impl Graph {
     pub fn set_x_label_margin(&mut self, x_label_margin: usize) -> &mut Self {
         self.x_label_margin = x_label_margin;
 
+        // the ticks of time series depend on it
+        self.clear_cache();
+
         self
     }
 
@@ -236,10 +396,27 @@ impl Graph {
         self
     }
 
+    /// Only box plots can be drawn horizontally. With `Orientation::Horizontal`, `y_min`, `y_max` and `pretty_y`
+    /// are applied to the x axis, and skip ranges are not supported: `SkipValue::Automatic` and the skip policy are ignored.
+    ///
+    /// It panics if it's `Orientation::Horizontal` and the skip range is `SkipValue::Manual`.
+    pub fn set_orientation(&mut self, orientation: Orientation) -> &mut Self {
+        assert!(
+            orientation == Orientation::Vertical || !self.skip_value.is_manual(),
+            "skip ranges are not supported with `Orientation::Horizontal`",
+        );
+
+        self.orientation = orientation;
+        self.adjust_all_labeled_intervals();
+
+        self
+    }
+
     /// It sets `self.plot_width = self.data.len() * block_width`. If the `plot_width` is already set, it overrides it.
-    /// It only works with 1-dimensional data.
+    /// It only works with 1-dimensional data and vertical box plots.
     pub fn set_block_width(&mut self, block_width: usize) -> &mut Self {
         self.block_width = Some(block_width);
+        self.clear_cache();
         self.adjust_all_labeled_intervals();
 
         self
@@ -306,30 +483,68 @@ impl Graph {
         self
     }
 
-    /// It does not plot data between this range. It's applied only when the plot height is greater than 18.
+    /// How to reduce the data when it's longer than the plot width. See `Downsampling`.
+    pub fn set_downsampling(&mut self, downsampling: Downsampling) -> &mut Self {
+        self.downsampling = downsampling;
+        self.clear_cache();
+
+        self
+    }
+
+    /// It does not plot data between this range. It's applied only when the plot is at least as high as
+    /// the `min_plot_height` of the skip policy, which is default to 19.
+    ///
+    /// It panics if it's `SkipValue::Manual` and the orientation is `Orientation::Horizontal`. See `set_orientation`.
     pub fn set_skip_range(&mut self, skip_value: SkipValue) -> &mut Self {
+        assert!(
+            self.orientation == Orientation::Vertical || !skip_value.is_manual(),
+            "skip ranges are not supported with `Orientation::Horizontal`",
+        );
+
         self.skip_value = skip_value;
 
         self
     }
 
-    /// I need a better name for this.
-    ///
+    /// It decides when and how `SkipValue::Automatic` skips ranges, and how the height is split between the subplots.
+    /// Use `get_skipped_ranges` to see which ranges are skipped.
+    pub fn set_skip_policy(&mut self, skip_policy: SkipPolicy) -> &mut Self {
+        self.skip_policy = skip_policy;
+
+        self
+    }
+
     /// If the skip_range is included in this range, the skip_range is not set. It works only when its skip_value is `SkipValue::Automatic`.
-    /// You can set open ends with `None` values.
+    /// You can set open ends with `None` values. It sets `never_skip` of the current skip policy.
+    #[deprecated(note = "use `SkipPolicy::set_never_skip` with `set_skip_policy`")]
     pub fn set_skip_skip_range<T: TryInto<Ratio> + Clone, U: TryInto<Ratio> + Clone>(&mut self, from: Option<T>, to: Option<U>) -> &mut Self {
-        self.skip_skip_range = Some((
-            from.map(|n| n.clone().try_into().unwrap_or(Ratio::zero())),
-            to.map(|n| n.clone().try_into().unwrap_or(Ratio::zero())),
-        ));
+        self.skip_policy.set_never_skip(from, to);
+
+        self
+    }
+
+    /// It's like skip_range, but it skips x-axis not y-axis. It can be called multiple times to add multiple breaks.
+    /// `start` and `end` are both inclusive, and they're indices of `self.data`, like `add_labeled_interval`.
+    /// That means `self.data[start]` ~ `self.data[end]` are hidden, and a vertical line is drawn instead.
+    /// It works for 1-dimensional data, time series and vertical box plots. For time series, the columns from the timestamp
+    /// of `self.data[start]` to the timestamp of `self.data[end]` are hidden.
+    pub fn add_horizontal_break(&mut self, start: usize, end: usize) -> &mut Self {
+        self.horizontal_breaks.push((start.min(end), start.max(end)));
 
         self
     }
 
-    /// `from` and `to` are x indices of the output plot.
-    /// It's like skip_range, but it skips x-axis not y-axis.
+    /// It replaces all the horizontal breaks with a single break that hides `from` ~ `to - 1`.
+    ///
+    /// It used to take the columns of the plot, but now `from` and `to` are indices of `self.data`, like `add_horizontal_break`.
+    /// They're the same only if the data is as long as the plot is wide.
+    #[deprecated(note = "use `add_horizontal_break`, whose `end` is inclusive")]
     pub fn set_horizontal_break(&mut self, from: usize, to: usize) -> &mut Self {
-        self.horizontal_break = Some((from, to));
+        self.horizontal_breaks.clear();
+
+        if from < to {
+            self.add_horizontal_break(from, to - 1);
+        }
 
         self
     }
@@ -337,14 +552,60 @@ impl Graph {
     /// See `README.md` to see how it works. `start` and `end` are both inclusive.
     /// `start` and `end` corresponds to the index of `self.data`. That means if the interval is (0, 32),
     /// it's `self.data[0]` ~ `self.data[32]`. The actual number of the characters used depends on the size of the graph.
+    ///
+    /// For 2-dimensional data and heatmaps, `start` and `end` are the x coordinates of the plot.
     pub fn add_labeled_interval<T: ToString>(&mut self, start: i32, end: i32, label: T) -> &mut Self {
-        let mut interval = Interval::new(start, end, label.to_string());
+        self.labeled_intervals.push(Interval::new(start, end, label.to_string()));
+        self.adjust_all_labeled_intervals();
 
-        if !self.data.is_empty() {
-            interval.adjust_coordinate(self.get_actual_plot_width(), self.data.len());
-        }
+        self
+    }
+
+    /// It marks the columns of the labeled intervals in the plot area, so that you can see which bars they cover.
+    /// See `IntervalHighlight`. It's default to `IntervalHighlight::None`.
+    pub fn set_interval_highlight(&mut self, highlight: IntervalHighlight) -> &mut Self {
+        self.interval_highlight = highlight;
+
+        self
+    }
 
-        self.labeled_intervals.push(interval);
+    pub fn set_interval_highlight_color(&mut self, color: Option<Color>) -> &mut Self {
+        self.interval_highlight_color = color;
+
+        self
+    }
+
+    /// Dotted lines at the rows of the y labels and the columns of the x labels. They're drawn on the empty cells,
+    /// behind the bars. If the y axis is skipped, each subplot has its own horizontal gridlines. See `Gridlines`.
+    /// It works for 1-dimensional data, time series and vertical box plots. It's default to `Gridlines::None`.
+    pub fn set_gridlines(&mut self, gridlines: Gridlines) -> &mut Self {
+        self.gridlines = gridlines;
+
+        self
+    }
+
+    /// If it's not set, the gridlines are not colored. A dim color, like `Color::Dark`, makes them less noisy.
+    pub fn set_gridline_color(&mut self, color: Option<Color>) -> &mut Self {
+        self.gridline_color = color;
+
+        self
+    }
+
+    /// It draws `annotation` on the plot. See `Annotation`. It can be called multiple times, and the labels
+    /// of the annotations are placed so that they don't overlap each other.
+    /// The markers and the lines are drawn over the plot. If two annotations point at the same cell, the one that's added later is drawn.
+    /// It works for 1-dimensional data, time series and vertical box plots. If the data of the annotation is hidden by a horizontal break, it's not drawn.
+    pub fn add_annotation(&mut self, annotation: &Annotation) -> &mut Self {
+        self.annotations.push(annotation.clone());
+
+        self
+    }
+
+    /// It explains the glyphs and the colors of the graph. See `Legend`. It's default to `None`.
+    ///
+    /// Heatmaps draw their scale on the right side even if it's `None`. If it's set, the scale is one of the entries of the legend.
+    pub fn set_legend(&mut self, legend: Option<Legend>) -> &mut Self {
+        self.legend = legend;
 
         self
     }
@@ -355,17 +616,48 @@ impl Graph {
         self
     }
 
+    /// If it's set, the cells of a heatmap are colored with these colors (from the lowest to the highest) instead of shades.
+    /// With `ColorMode::TerminalBg`, the backgrounds of the cells are colored. It must not be empty.
+    pub fn set_heatmap_colors(&mut self, colors: Option<Vec<Color>>) -> &mut Self {
+        self.heatmap_colors = colors;
+
+        self
+    }
+
     pub fn set_color_mode(&mut self, color_mode: ColorMode) -> &mut Self {
         self.color_mode = color_mode;
 
         self
     }
 
+    /// If it's false, the y labels are not drawn, and the plot starts right at the y axis.
+    /// It's useful when the graph is merged with another graph that has the same y axis.
+    pub fn set_show_y_labels(&mut self, show_y_labels: bool) -> &mut Self {
+        self.show_y_labels = show_y_labels;
+
+        self
+    }
+
+    /// The minimum width of the y labels. If the labels are narrower, they're padded. It's used to align the y axes of vertically merged graphs.
+    pub fn set_y_label_width(&mut self, y_label_width: usize) -> &mut Self {
+        self.y_label_width = Some(y_label_width);
+
+        self
+    }
+
     pub fn set_y_label_formatter(&mut self, formatter: Box<dyn NumberFormatter>) -> &mut Self {
         self.y_label_formatter = formatter.into();
 
         self
     }
+
+    /// Use `Charset::Ascii` if your terminal (or CI log) cannot render unicode box-drawing characters.
+    /// It's applied to bars, borders, labeled intervals, breaks and 2-dimensional plots.
+    pub fn set_charset(&mut self, charset: Charset) -> &mut Self {
+        self.charset = charset;
+
+        self
+    }
 }
 
 impl Default for Graph {
@@ -374,7 +666,9 @@ impl Default for Graph {
             plot_width: 80,
             plot_height: 28,
             block_width: None,
+            orientation: Orientation::Vertical,
             data: GraphData::None,
+            data_range: None,
             x_label_margin: 2,
             y_label_margin: 2,
             paddings: [0; 4],
@@ -382,17 +676,30 @@ impl Default for Graph {
             y_min: None,
             pretty_y: Some(Ratio::try_from(0.5).unwrap()),
             y_label_formatter: Arc::new(DefaultFormatter),
+            show_y_labels: true,
+            y_label_width: None,
+            time_range: None,
             title: None,
             title_color: None,
+            downsampling: Downsampling::MinMax,
             skip_value: SkipValue::Automatic,
-            skip_skip_range: None,
-            horizontal_break: None,
+            skip_policy: SkipPolicy::default(),
+            horizontal_breaks: vec![],
             x_axis_label: None,
             y_axis_label: None,
             labeled_intervals: vec![],
+            interval_highlight: IntervalHighlight::None,
+            interval_highlight_color: None,
+            annotations: vec![],
+            legend: None,
+            gridlines: Gridlines::None,
+            gridline_color: None,
             big_title: false,
             color_mode: ColorMode::None,
             primary_color: None,
+            heatmap_colors: None,
+            charset: Charset::Unicode,
+            cache: DataCache::default(),
         }
     }
 }
@@ -0,0 +1,118 @@
+use super::{Graph, GraphData, prettify_y_labels, unwrap_y_min_max};
+use crate::skip_value::SkipValue;
+use hmath::Ratio;
+
+/// Options for `share_axes`. The y range is always shared.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SharedAxes {
+    /// Time series share the time range, so that the same column means the same time.
+    pub x_range: bool,
+
+    /// The y labels have the same width, so that the y axes are aligned when the graphs are merged vertically.
+    pub y_label_width: bool,
+
+    /// Only the first graph shows its y labels. Use it when the graphs are merged horizontally.
+    pub hide_inner_y_labels: bool,
+}
+
+/// It links the graphs so that they share the y range. Without this, each graph chooses its own range,
+/// and the bars of the graphs are not comparable when they're merged.
+///
+/// It sets `y_min` and `y_max` of the graphs (and `time_range`, `y_label_width` and `show_y_labels`, depending on `options`),
+/// so call this after the data and the other settings are set. The explicitly set `y_min` and `y_max` are respected.
+/// The graphs don't skip ranges automatically anymore, because each graph would skip a different range.
+/// 2-dimensional graphs and horizontal box plots are not affected.
+pub fn share_axes(graphs: &mut [&mut Graph], options: SharedAxes) {
+    // The time range is unified first, because the y range of a time series depends on its time range.
+    if options.x_range {
+        let mut time_range: Option<(i64, i64)> = None;
+
+        for graph in graphs.iter() {
+            if let GraphData::TimeSeries(data) = &graph.data {
+                if data.is_empty() {
+                    continue;
+                }
+
+                let (from, to) = graph.get_time_range(data);
+
+                time_range = match time_range {
+                    Some((from_, to_)) => Some((from.min(from_), to.max(to_))),
+                    None => Some((from, to)),
+                };
+            }
+        }
+
+        if let Some((from, to)) = time_range {
+            for graph in graphs.iter_mut() {
+                if let GraphData::TimeSeries(_) = &graph.data {
+                    graph.set_time_range(from, to);
+                }
+            }
+        }
+    }
+
+    let mut y_range: Option<(Ratio, Ratio)> = None;
+    let mut y_min_movable = true;
+    let mut y_max_movable = true;
+
+    for graph in graphs.iter() {
+        let (data_min, data_max) = match graph.get_data_y_range() {
+            Some(range) => range,
+            None => { continue; },
+        };
+        let (graph_y_min, graph_y_max) = unwrap_y_min_max(&graph.y_min, &graph.y_max, &data_min, &data_max);
+        y_min_movable &= graph.y_min.is_none();
+        y_max_movable &= graph.y_max.is_none();
+
+        y_range = match y_range {
+            Some((y_min, y_max)) => Some((
+                if graph_y_min.lt_rat(&y_min) { graph_y_min } else { y_min },
+                if graph_y_max.gt_rat(&y_max) { graph_y_max } else { y_max },
+            )),
+            None => Some((graph_y_min, graph_y_max)),
+        };
+    }
+
+    let (y_min, y_max) = match y_range {
+        Some(range) => range,
+        None => { return; },
+    };
+
+    // the graphs with a y axis
+    let mut graphs = graphs.iter_mut().filter(|graph| graph.get_data_y_range().is_some()).collect::<Vec<_>>();
+
+    // the lowest graph has the fewest rows for the labels, so the labels that fit in it fit in the others too
+    let plot_height = graphs.iter().map(|graph| graph.plot_height).min().unwrap_or(0);
+
+    let (y_min, y_max) = prettify_y_labels(
+        &y_min,
+        &y_max,
+        plot_height,
+        graphs[0].pretty_y.as_ref().map(|n| (y_min_movable, y_max_movable, n.clone())),
+    );
+
+    for graph in graphs.iter_mut() {
+        graph.y_min = Some(y_min.clone());
+        graph.y_max = Some(y_max.clone());
+
+        if graph.skip_value.is_automatic() {
+            graph.skip_value = SkipValue::None;
+        }
+    }
+
+    if options.y_label_width {
+        let width = graphs.iter().map(
+            |graph| graph.get_y_labels_width(&y_min, &y_max).max(graph.y_label_width.unwrap_or(0))
+        ).max().unwrap_or(0);
+
+        for graph in graphs.iter_mut() {
+            graph.y_label_width = Some(width);
+        }
+    }
+
+    if options.hide_inner_y_labels {
+        for graph in graphs.iter_mut().skip(1) {
+            graph.show_y_labels = false;
+        }
+    }
+}
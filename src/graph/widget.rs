@@ -0,0 +1,149 @@
+use super::{Graph, GraphData};
+use crate::canvas::Canvas;
+use crate::color::ColorMode;
+use crate::lines::{Grapheme, Lines, strip_escape_sequences};
+use crate::orientation::Orientation;
+use ratatui_core::buffer::Buffer;
+use ratatui_core::layout::Rect;
+use ratatui_core::style::{Color, Style};
+use ratatui_core::widgets::Widget;
+
+/// It draws the graph into `buf`, at `area`. Only available with the `ratatui` feature.
+///
+/// The plot is resized so that the whole output, including the labels and the title, fits in `area`.
+/// 2-dimensional graphs and heatmaps are not resized because their size is decided by the labels,
+/// and neither is the width of a graph with `block_width`. If the output is still larger than `area`, it's cropped.
+///
+/// The colors are drawn as the background colors if the color mode is `ColorMode::TerminalBg`, and the foreground colors otherwise.
+/// If the graph is not valid (see `Graph::is_valid`), it draws an error message instead.
+///
+/// ```nohighlight
+/// terminal.draw(|frame| frame.render_widget(&graph, frame.area()))?;
+/// ```
+impl Widget for &Graph {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.intersection(buf.area);
+
+        if area.is_empty() {
+            return;
+        }
+
+        if !self.is_valid() {
+            buf.set_stringn(area.x, area.y, INVALID_GRAPH_MESSAGE, area.width as usize, Style::default());
+            return;
+        }
+
+        let lines = self.fit_to_area(area.width as usize, area.height as usize);
+        render_lines(&lines, &self.color_mode, area, buf);
+    }
+}
+
+impl Widget for Graph {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        (&self).render(area, buf);
+    }
+}
+
+/// It draws the canvas into `buf`, at `area`. Only available with the `ratatui` feature.
+///
+/// The canvas is not resized. If it's larger than `area`, it's cropped. The colors are drawn like `Graph`.
+impl Widget for &Canvas {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.intersection(buf.area);
+
+        if area.is_empty() {
+            return;
+        }
+
+        render_lines(self.get_lines(), self.get_color_mode(), area, buf);
+    }
+}
+
+impl Widget for Canvas {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        (&self).render(area, buf);
+    }
+}
+
+// `area` must be in `buf`.
+fn render_lines(lines: &Lines, color_mode: &ColorMode, area: Rect, buf: &mut Buffer) {
+    let is_bg = matches!(color_mode, ColorMode::TerminalBg);
+
+    for y in 0..lines.get_height().min(area.height as usize) {
+        for x in 0..lines.get_width().min(area.width as usize) {
+            let cell = &mut buf[(area.x + x as u16, area.y + y as u16)];
+
+            match lines.get(x, y) {
+                Grapheme::Char(c) => { cell.set_char(*c); },
+                Grapheme::Cluster(s) => { cell.set_symbol(&strip_escape_sequences(s)); },
+
+                // ratatui resets the cells that are hidden by a double-width grapheme
+                Grapheme::WideTail => {
+                    cell.reset();
+                    continue;
+                },
+            }
+
+            if let Some(color) = lines.get_color(x, y) {
+                let (r, g, b) = color.get_rgb();
+
+                if is_bg {
+                    cell.bg = Color::Rgb(r, g, b);
+                }
+
+                else {
+                    cell.fg = Color::Rgb(r, g, b);
+                }
+            }
+        }
+    }
+}
+
+const INVALID_GRAPH_MESSAGE: &str = "invalid graph";
+
+impl Graph {
+    // The size of the labels depends on the size of the plot, so it has to draw the graph a few times.
+    // It returns the output of the last attempt, so that the caller doesn't have to draw it again.
+    fn fit_to_area(&self, width: usize, height: usize) -> Lines {
+        let (resize_width, resize_height) = match &self.data {
+            GraphData::Data2D { .. } | GraphData::Heatmap { .. } => { return self.draw_lines(); },
+            GraphData::Data1D(_) => (self.block_width.is_none(), true),
+            GraphData::BoxPlot(_) => (self.block_width.is_none() || self.orientation == Orientation::Horizontal, true),
+            _ => (true, true),
+        };
+        let mut result = self.clone();
+        let mut lines = result.draw_lines();
+
+        for _ in 0..3 {
+            if fits(&lines, width, height) {
+                break;
+            }
+
+            let mut resized = false;
+
+            if resize_width && lines.get_width() != width {
+                let plot_width = (result.plot_width + width).saturating_sub(lines.get_width()).max(1);
+                resized |= plot_width != result.plot_width;
+                result.set_plot_width(plot_width);
+            }
+
+            if resize_height && lines.get_height() != height {
+                let plot_height = (result.plot_height + height).saturating_sub(lines.get_height()).max(1);
+                resized |= plot_height != result.plot_height;
+                result.set_plot_height(plot_height);
+            }
+
+            if !resized {
+                break;
+            }
+
+            lines = result.draw_lines();
+        }
+
+        lines
+    }
+}
+
+fn fits(lines: &Lines, width: usize, height: usize) -> bool {
+    lines.get_width() == width && lines.get_height() == height
+}
@@ -0,0 +1,80 @@
+use crate::charset::Glyphs;
+use crate::color::Color;
+use crate::layout::Rect;
+use crate::lines::{Grapheme, Lines};
+use std::str::FromStr;
+
+/// Dotted lines in the plot area, so that it's easier to read the values of the bars. See `Graph::set_gridlines`.
+///
+/// It's default to `Gridlines::None`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Gridlines {
+    #[default]
+    None,
+
+    /// at the rows of the y labels
+    Horizontal,
+
+    /// At the columns of the x labels. The x labels alternate between 2 lines, and only the labels on the first line have gridlines.
+    Vertical,
+
+    Both,
+}
+
+impl Gridlines {
+    fn has_horizontal(&self) -> bool {
+        matches!(self, Gridlines::Horizontal | Gridlines::Both)
+    }
+
+    fn has_vertical(&self) -> bool {
+        matches!(self, Gridlines::Vertical | Gridlines::Both)
+    }
+}
+
+impl FromStr for Gridlines {
+    type Err = String;
+
+    /// returns Err(s) if it fails
+    fn from_str(s: &str) -> Result<Gridlines, String> {
+        match s.replace(" ", "").replace("_", "").replace("-", "").to_ascii_lowercase() {
+            s if s == "none" => Ok(Gridlines::None),
+            s if s == "horizontal" => Ok(Gridlines::Horizontal),
+            s if s == "vertical" => Ok(Gridlines::Vertical),
+            s if s == "both" => Ok(Gridlines::Both),
+            _ => Err(s.to_string()),
+        }
+    }
+}
+
+// It draws the gridlines on the empty cells of `area` of `plot`, so that they're behind the bars. `rows` and `columns` are
+// in the coordinates of `plot`. Where a horizontal and a vertical gridline cross, the vertical one is drawn.
+pub fn draw_gridlines(
+    plot: &mut Lines,
+    gridlines: Gridlines,
+    rows: &[usize],
+    columns: &[usize],
+    area: &Rect,
+    color: Option<Color>,
+    glyphs: &Glyphs,
+) {
+    let mut cells = vec![];
+
+    if gridlines.has_vertical() {
+        for x in columns.iter() {
+            cells.extend((area.y..(area.y + area.height)).map(|y| (*x, y, glyphs.dotted_vertical)));
+        }
+    }
+
+    if gridlines.has_horizontal() {
+        for y in rows.iter() {
+            cells.extend((area.x..(area.x + area.width)).map(|x| (x, *y, glyphs.dotted_horizontal)));
+        }
+    }
+
+    for (x, y, c) in cells.into_iter() {
+        if area.contains(x, y) && *plot.get(x, y) == Grapheme::Char(' ') && plot.get_color(x, y).is_none() {
+            plot.set(x, y, c);
+            plot.set_color(x, y, color.clone());
+        }
+    }
+}
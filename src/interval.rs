@@ -1,6 +1,42 @@
 use crate::alignment::Alignment;
-use crate::color::ColorMode;
-use crate::lines::Lines;
+use crate::charset::Glyphs;
+use crate::color::{Color, ColorMode};
+use crate::layout::Rect;
+use crate::lines::{Grapheme, Lines};
+use crate::utils::{display_width, truncate_to_width};
+use std::str::FromStr;
+
+/// How the labeled intervals are marked in the plot area, in addition to the arrows below the x labels.
+/// See `Graph::set_interval_highlight`.
+///
+/// It's default to `IntervalHighlight::None`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum IntervalHighlight {
+    /// only the arrows
+    #[default]
+    None,
+
+    /// The empty cells in the columns of an interval are filled with the lightest shade of the charset.
+    /// If the color of the highlight is set and the color mode is `ColorMode::TerminalBg`, the backgrounds of the cells are colored instead.
+    Shade,
+
+    /// Dotted vertical lines at the first and the last columns of an interval, on the empty cells.
+    Boundaries,
+}
+
+impl FromStr for IntervalHighlight {
+    type Err = String;
+
+    /// returns Err(s) if it fails
+    fn from_str(s: &str) -> Result<IntervalHighlight, String> {
+        match s.replace(" ", "").replace("_", "").replace("-", "").to_ascii_lowercase() {
+            s if s == "none" => Ok(IntervalHighlight::None),
+            s if s == "shade" => Ok(IntervalHighlight::Shade),
+            s if s == "boundaries" => Ok(IntervalHighlight::Boundaries),
+            _ => Err(s.to_string()),
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct Interval {
@@ -29,9 +65,9 @@ impl Interval {
         self.end >= self.start
     }
 
-    pub fn label_to_v16(&self) -> Vec<u16> {
-        self.label.encode_utf16().map(
-            |c| c.max(' ' as u16)  // replace newline characters
+    pub fn label_to_string(&self) -> String {
+        self.label.chars().map(
+            |c| c.max(' ')  // replace newline characters
         ).collect()
     }
 
@@ -43,48 +79,58 @@ impl Interval {
         self.plot_end = end * graph_width / data_size;
     }
 
-    pub fn render_full(&self) -> Vec<u16> {
-        let label = self.label_to_v16();
+    // `f` maps an index of the data to a column of the plot. It's for the data whose columns are not proportional to the indices, like time series.
+    pub fn adjust_coordinate_by<F: Fn(i32) -> usize>(&mut self, f: F) {
+        self.plot_start = f(self.start);
+        self.plot_end = f(self.end);
+    }
+
+    // It returns a copy of this interval, whose `plot_start` and `plot_end` are moved by `f`.
+    // The second argument of `f` is true for `plot_end`.
+    pub fn map_coordinate<F: Fn(usize, bool) -> usize>(&self, f: F) -> Self {
+        Interval {
+            plot_start: f(self.plot_start, false),
+            plot_end: f(self.plot_end, true),
+            ..self.clone()
+        }
+    }
+
+    pub fn render_full(&self, glyphs: &Glyphs) -> String {
+        let label = self.label_to_string();
+        let label_width = display_width(&label);
+        let (line, start, end) = (glyphs.horizontal.to_string(), glyphs.arrows[0], glyphs.arrows[1]);
         let len = self.plot_end - self.plot_start + 1;  // inclusive end
 
-        if len >= label.len() + 4 {
-            let rem = len - label.len() - 2;
+        if len >= label_width + 4 {
+            let rem = len - label_width - 2;
             let left = rem / 2;
             let right = rem / 2 + rem % 2;
-            vec![
-                vec!['<' as u16],
-                vec!['─' as u16; left],
-                label,
-                vec!['─' as u16; right],
-                vec!['>' as u16],
-            ].concat()
+
+            format!("{start}{}{label}{}{end}", line.repeat(left), line.repeat(right))
         }
 
-        else if label.len() > 8 && len > 7 {
-            vec![
-                vec!['<' as u16, '─' as u16],
-                label[0..(len - 7)].to_vec(),
-                vec!['.' as u16; 3],
-                vec!['─' as u16, '>' as u16],
-            ].concat()
+        else if label_width > 8 && len > 7 {
+            let label = truncate_to_width(&label, len - 7);
+
+            // if a double-width character is cut, the truncated label is narrower than `len - 7`
+            let dots = ".".repeat(3 + len - 7 - display_width(&label));
+
+            format!("{start}{line}{label}{dots}{line}{end}")
         }
 
         else if len > 1 {
-            vec![
-                vec!['<' as u16],
-                vec!['─' as u16; len - 2],
-                vec!['>' as u16],
-            ].concat()
+            format!("{start}{}{end}", line.repeat(len - 2))
         }
 
         // Too small to draw
         else {
-            vec![]
+            String::new()
         }
     }
 
-    pub fn render_half(&self, left_side: bool, graph_width: usize) -> Vec<u16> {
-        let mut label = self.label_to_v16();
+    pub fn render_half(&self, left_side: bool, graph_width: usize, glyphs: &Glyphs) -> String {
+        let mut label = self.label_to_string();
+        let (line, start, end) = (glyphs.horizontal.to_string(), glyphs.arrows[0], glyphs.arrows[1]);
 
         // `+1` for inclusive range
         let len = if left_side {
@@ -93,44 +139,29 @@ impl Interval {
             graph_width - self.plot_start
         };
 
-        if len < label.len() + 2 {
+        if len < display_width(&label) + 2 {
             if len > 5 {
-                label = vec![
-                    label[..(len - 5)].to_vec(),
-                    vec!['.' as u16; 3],
-                ].concat()
+                label = format!("{}...", truncate_to_width(&label, len - 5));
             }
 
             else {
-                label = vec![];
+                label = String::new();
             }
         }
 
-        let label_len = label.len();
+        let label_len = display_width(&label);
 
-        vec![
-            if left_side {
-                vec![]
-            } else {
-                vec![
-                    vec!['<' as u16],
-                    vec!['─' as u16; len - label_len - 1],
-                ].concat()
-            },
-            label,
-            if left_side {
-                vec![
-                    vec!['─' as u16; len - label_len - 1],
-                    vec!['>' as u16],
-                ].concat()
-            } else {
-                vec![]
-            },
-        ].concat()
+        if left_side {
+            format!("{label}{}{end}", line.repeat(len - label_len - 1))
+        }
+
+        else {
+            format!("{start}{}{label}", line.repeat(len - label_len - 1))
+        }
     }
 }
 
-pub fn draw_labeled_intervals(intervals: &Vec<Interval>, graph_width: usize) -> Lines {
+pub fn draw_labeled_intervals(intervals: &[Interval], graph_width: usize, glyphs: &Glyphs) -> Lines {
     let mut masks = vec![vec![false; graph_width]];
     let mut rows = vec![vec![]];
 
@@ -159,21 +190,21 @@ pub fn draw_labeled_intervals(intervals: &Vec<Interval>, graph_width: usize) ->
     for (index, row) in rows.iter().enumerate() {
         for interval in row.iter() {
             if interval.start < 0 {
-                let i = interval.render_half(true, graph_width);
-                let l = Lines::from_string(&String::from_utf16_lossy(&i), Alignment::First, &ColorMode::None);
-                result = result.blit(&l, 0, index, None);
+                let i = interval.render_half(true, graph_width, glyphs);
+                let l = Lines::from_string(&i, Alignment::First, &ColorMode::None);
+                result.blit_mut(&l, 0, index, None);
             }
 
             else if interval.plot_end >= graph_width {
-                let i = interval.render_half(false, graph_width);
-                let l = Lines::from_string(&String::from_utf16_lossy(&i), Alignment::First, &ColorMode::None);
-                result = result.blit(&l, interval.plot_start, index, None);
+                let i = interval.render_half(false, graph_width, glyphs);
+                let l = Lines::from_string(&i, Alignment::First, &ColorMode::None);
+                result.blit_mut(&l, interval.plot_start, index, None);
             }
 
             else {
-                let i = interval.render_full();
-                let l = Lines::from_string(&String::from_utf16_lossy(&i), Alignment::First, &ColorMode::None);
-                result = result.blit(&l, interval.plot_start, index, None);
+                let i = interval.render_full(glyphs);
+                let l = Lines::from_string(&i, Alignment::First, &ColorMode::None);
+                result.blit_mut(&l, interval.plot_start, index, None);
             }
         }
     }
@@ -181,18 +212,68 @@ pub fn draw_labeled_intervals(intervals: &Vec<Interval>, graph_width: usize) ->
     result
 }
 
-fn can_push(mask: &Vec<bool>, interval: &Interval) -> bool {
+// It marks the columns of `intervals` on the empty cells of `area` of `plot`. The columns of the intervals are relative to `area`,
+// like `draw_labeled_intervals`, whose `graph_width` is `area.width`. The cells that are already drawn (bars, dots, other highlights) are not changed.
+pub fn highlight_labeled_intervals(
+    plot: &mut Lines,
+    intervals: &[Interval],
+    area: &Rect,
+    highlight: IntervalHighlight,
+    color: Option<Color>,
+    color_mode: &ColorMode,
+    glyphs: &Glyphs,
+) {
+    let c = match highlight {
+        IntervalHighlight::None => { return; },
+        IntervalHighlight::Shade if color.is_some() && matches!(color_mode, ColorMode::TerminalBg) => ' ',
+        IntervalHighlight::Shade => glyphs.shades[0],
+        IntervalHighlight::Boundaries => glyphs.dotted_vertical,
+    };
+
+    for interval in intervals.iter() {
+        if interval.end < 0 || interval.plot_start >= area.width {
+            continue;
+        }
+
+        let columns = match highlight {
+            IntervalHighlight::Boundaries => {
+                let mut columns = vec![];
+
+                // the open sides of the intervals that go out of the graph don't have boundaries
+                if interval.start >= 0 {
+                    columns.push(interval.plot_start);
+                }
+
+                if interval.plot_end < area.width {
+                    columns.push(interval.plot_end);
+                }
+
+                columns
+            },
+            _ => (interval.plot_start..=interval.plot_end.min(area.width - 1)).collect(),
+        };
+
+        for x in columns.into_iter().map(|x| area.x + x) {
+            for y in area.y..(area.y + area.height) {
+                if *plot.get(x, y) == Grapheme::Char(' ') && plot.get_color(x, y).is_none() {
+                    plot.set(x, y, c);
+                    plot.set_color(x, y, color.clone());
+                }
+            }
+        }
+    }
+}
+
+fn can_push(mask: &[bool], interval: &Interval) -> bool {
     let start = interval.plot_start;
     let end = interval.plot_end.min(mask.len() - 1);
 
     mask[start..(end + 1)].iter().all(|c| !c)
 }
 
-fn push(mask: &mut Vec<bool>, interval: &Interval) {
+fn push(mask: &mut [bool], interval: &Interval) {
     let start = interval.plot_start;
     let end = interval.plot_end.min(mask.len() - 1);
 
-    for i in start..(end + 1) {
-        mask[i] = true;
-    }
+    mask[start..(end + 1)].fill(true);
 }
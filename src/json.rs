@@ -1,10 +1,19 @@
 use crate::{
+    Charset,
     Color,
     ColorMode,
     DefaultFormatter,
+    Downsampling,
     Error,
     Graph,
+    Gridlines,
+    HeightAllocation,
+    IntervalHighlight,
+    Legend,
+    LegendPosition,
     NumberFormatter,
+    Orientation,
+    SkipPolicy,
     SkipValue,
 };
 use crate::error::{JsonType, get_type};
@@ -36,6 +45,10 @@ impl Graph {
     ///
     /// - 1d_data: Array[Number]
     /// - 1d_labeled_data: Array[[String, Number]]
+    /// - time_series_data: Array[[Integer, Number]]
+    ///   - (unix timestamp in seconds, value)
+    /// - box_plot_data: Array[[String, Array[Number]]]
+    ///   - (category, samples)
     /// - y_min: Number
     /// - y_max: Number
     /// - y_range: [Number, Number]
@@ -56,14 +69,46 @@ impl Graph {
     ///   - <<https://docs.rs/asciigraph/latest/asciigraph/enum.Color.html>>
     /// - color_mode: String
     ///   - <<https://docs.rs/asciigraph/latest/asciigraph/enum.ColorMode.html>>
-    /// - skip_range: Optional[[Number, Number]]
+    /// - charset: String
+    ///   - "unicode" or "ascii"
+    /// - downsampling: String
+    ///   - "min_max", "mean", "median", "last", "sum" or "lttb"
+    ///   - <<https://docs.rs/asciigraph/latest/asciigraph/enum.Downsampling.html>>
+    /// - orientation: String
+    ///   - "vertical" or "horizontal"
+    /// - skip_range: Optional[[Number, Number] | Array[[Number, Number]]]
     ///   - if it's not set, it's default to `SkipValue::Automatic`
     ///   - if you want it to be `SkipValue::None`, set this value to null
-    ///   - otherwise, it's set to `SkipValue::Manual { from: v[0], to: v[1] }`
+    ///   - if it's a pair of numbers, it's set to `SkipValue::Manual(vec![(v[0], v[1])])`
+    ///   - if it's an array of pairs, each pair is skipped
+    ///   - `from <= to` for each pair, and the pairs must not overlap
+    /// - skip_policy: Object
+    ///   - min_plot_height: Integer
+    ///   - min_gap_ratio: Number, must be positive
+    ///   - max_ranges: Optional[Integer]
+    ///   - padding: Number, must be positive
+    ///   - min_subplot_height: Integer
+    ///   - height_allocation: String
+    ///     - "equal", "density" or "range"
+    ///   - never_skip: [Optional[Number], Optional[Number]]
+    ///   - the omitted keys are default values of `SkipPolicy`
     /// - y_label_prefix: String
     /// - y_label_suffix: String
     /// - labeled_intervals: Array[[Integer, Integer, String]]
-    /// - horizontal_break: [Integer, Integer]
+    /// - interval_highlight: String
+    ///   - "none", "shade" or "boundaries"
+    /// - interval_highlight_color: String
+    ///   - <<https://docs.rs/asciigraph/latest/asciigraph/enum.Color.html>>
+    /// - legend: String
+    ///   - "right", "bottom", "top left", "top right", "bottom left" or "bottom right"
+    /// - gridlines: String
+    ///   - "none", "horizontal", "vertical" or "both"
+    /// - gridline_color: String
+    ///   - <<https://docs.rs/asciigraph/latest/asciigraph/enum.Color.html>>
+    /// - horizontal_breaks: Array[[Integer, Integer]]
+    ///   - indices of the data, see `Graph::add_horizontal_break`
+    /// - horizontal_break: removed
+    ///   - it used to be the columns of the plot, and it returns `Error::RemovedKey`. Use `horizontal_breaks` instead.
     ///
     /// For `Number`s in the above type annotations,
     ///
@@ -156,6 +201,121 @@ impl Graph {
                             });
                         }
                     },
+                    "time_series_data" => {
+                        if value.is_array() {
+                            let mut timestamps_and_numbers = vec![];
+
+                            for member in value.members() {
+                                match member {
+                                    JsonValue::Array(timestamp_and_number) => {
+                                        if timestamp_and_number.len() == 2 {
+                                            let timestamp = if let Some(t) = timestamp_and_number[0].as_i64() {
+                                                t
+                                            } else {
+                                                return Err(Error::JsonTypeError {
+                                                    key: Some(key.to_string()),
+                                                    expected: JsonType::Integer,
+                                                    got: get_type(&timestamp_and_number[0]),
+                                                });
+                                            };
+                                            let number = json_to_ratio(&timestamp_and_number[1])?;
+
+                                            timestamps_and_numbers.push((timestamp, number));
+                                        }
+
+                                        else {
+                                            return Err(Error::JsonArrayLengthError {
+                                                key: Some(key.to_string()),
+                                                expected: 2,
+                                                got: timestamp_and_number.len(),
+                                            });
+                                        }
+                                    },
+                                    _ => {
+                                        return Err(Error::JsonTypeError {
+                                            key: Some(key.to_string()),
+                                            expected: JsonType::Array(Box::new(JsonType::Any)),
+                                            got: get_type(member),
+                                        });
+                                    },
+                                }
+                            }
+
+                            result.set_time_series_data(&timestamps_and_numbers);
+                        }
+
+                        else {
+                            return Err(Error::JsonTypeError {
+                                key: Some(key.to_string()),
+                                expected: JsonType::Array(Box::new(JsonType::Array(Box::new(JsonType::Any)))),
+                                got: get_type(value),
+                            });
+                        }
+                    },
+                    "box_plot_data" => {
+                        if value.is_array() {
+                            let mut labels_and_samples = vec![];
+
+                            for member in value.members() {
+                                match member {
+                                    JsonValue::Array(label_and_samples) => {
+                                        if label_and_samples.len() == 2 {
+                                            let label = if let Some(s) = label_and_samples[0].as_str() {
+                                                s.to_string()
+                                            } else {
+                                                return Err(Error::JsonTypeError {
+                                                    key: Some(key.to_string()),
+                                                    expected: JsonType::String,
+                                                    got: get_type(&label_and_samples[0]),
+                                                });
+                                            };
+
+                                            if !label_and_samples[1].is_array() {
+                                                return Err(Error::JsonTypeError {
+                                                    key: Some(key.to_string()),
+                                                    expected: JsonType::Array(Box::new(JsonType::Number)),
+                                                    got: get_type(&label_and_samples[1]),
+                                                });
+                                            }
+
+                                            let mut samples = vec![];
+
+                                            for n in label_and_samples[1].members() {
+                                                samples.push(json_to_ratio(n)?);
+                                            }
+
+                                            labels_and_samples.push((label, samples));
+                                        }
+
+                                        else {
+                                            return Err(Error::JsonArrayLengthError {
+                                                key: Some(key.to_string()),
+                                                expected: 2,
+                                                got: label_and_samples.len(),
+                                            });
+                                        }
+                                    },
+                                    _ => {
+                                        return Err(Error::JsonTypeError {
+                                            key: Some(key.to_string()),
+                                            expected: JsonType::Array(Box::new(JsonType::Any)),
+                                            got: get_type(member),
+                                        });
+                                    },
+                                }
+                            }
+
+                            result.set_box_plot_data(&labels_and_samples);
+                        }
+
+                        else {
+                            return Err(Error::JsonTypeError {
+                                key: Some(key.to_string()),
+                                expected: JsonType::Array(Box::new(JsonType::Array(Box::new(JsonType::Any)))),
+                                got: get_type(value),
+                            });
+                        }
+                    },
                     "y_min" => {
                         result.set_y_min(json_to_ratio(value)?);
                     },
@@ -167,8 +327,8 @@ impl Graph {
 
                         if values.len() == 2 {
                             result.set_y_range(
-                                json_to_ratio(&values[0])?,
-                                json_to_ratio(&values[1])?,
+                                json_to_ratio(values[0])?,
+                                json_to_ratio(values[1])?,
                             );
                         }
 
@@ -336,7 +496,7 @@ impl Graph {
                         Some(color) => {
                             result.set_title_color(
                                 Some(Color::from_str(color).map_err(
-                                    |e| Error::InvalidColorName(e)
+                                    Error::InvalidColorName
                                 )?)
                             );
                         },
@@ -352,7 +512,7 @@ impl Graph {
                         Some(color) => {
                             result.set_primary_color(
                                 Some(Color::from_str(color).map_err(
-                                    |e| Error::InvalidColorName(e)
+                                    Error::InvalidColorName
                                 )?)
                             );
                         },
@@ -368,7 +528,39 @@ impl Graph {
                         Some(color_mode) => {
                             result.set_color_mode(
                                 ColorMode::from_str(color_mode).map_err(
-                                    |e| Error::InvalidColorMode(e)
+                                    Error::InvalidColorMode
+                                )?
+                            );
+                        },
+                        _ => {
+                            return Err(Error::JsonTypeError {
+                                key: Some(key.to_string()),
+                                expected: JsonType::String,
+                                got: get_type(value),
+                            });
+                        },
+                    },
+                    "charset" => match value.as_str() {
+                        Some(charset) => {
+                            result.set_charset(
+                                Charset::from_str(charset).map_err(
+                                    Error::InvalidCharset
+                                )?
+                            );
+                        },
+                        _ => {
+                            return Err(Error::JsonTypeError {
+                                key: Some(key.to_string()),
+                                expected: JsonType::String,
+                                got: get_type(value),
+                            });
+                        },
+                    },
+                    "downsampling" => match value.as_str() {
+                        Some(downsampling) => {
+                            result.set_downsampling(
+                                Downsampling::from_str(downsampling).map_err(
+                                    Error::InvalidDownsampling
                                 )?
                             );
                         },
@@ -380,28 +572,59 @@ impl Graph {
                             });
                         },
                     },
+                    "orientation" => match value.as_str() {
+                        Some(orientation) => {
+                            let orientation = Orientation::from_str(orientation).map_err(
+                                Error::InvalidOrientation
+                            )?;
+
+                            if orientation == Orientation::Horizontal && result.get_skip_value().is_manual() {
+                                return Err(Error::UnsupportedSkipRange);
+                            }
+
+                            result.set_orientation(orientation);
+                        },
+                        _ => {
+                            return Err(Error::JsonTypeError {
+                                key: Some(key.to_string()),
+                                expected: JsonType::String,
+                                got: get_type(value),
+                            });
+                        },
+                    },
                     "skip_range" => match value {
                         JsonValue::Null => {
                             result.set_skip_range(SkipValue::None);
                         },
-                        JsonValue::Array(numbers) => {
-                            if numbers.len() == 2 {
-                                let from = json_to_ratio(&numbers[0])?;
-                                let to = json_to_ratio(&numbers[1])?;
+                        JsonValue::Array(_) if result.get_orientation() == Orientation::Horizontal => {
+                            return Err(Error::UnsupportedSkipRange);
+                        },
+                        // [Number, Number]
+                        JsonValue::Array(numbers) if !numbers.iter().any(|n| n.is_array()) => {
+                            let range = json_to_skip_range(numbers, key)?;
 
-                                result.set_skip_range(SkipValue::Manual {
-                                    from,
-                                    to,
-                                });
-                            }
+                            result.set_skip_range(SkipValue::Manual(check_skip_ranges(vec![range])?));
+                        },
+                        // [[Number, Number]]
+                        JsonValue::Array(ranges) => {
+                            let mut skip_ranges = Vec::with_capacity(ranges.len());
 
-                            else {
-                                return Err(Error::JsonArrayLengthError {
-                                    key: Some(key.to_string()),
-                                    expected: 2,
-                                    got: numbers.len(),
-                                });
+                            for range in ranges.iter() {
+                                match range {
+                                    JsonValue::Array(numbers) => {
+                                        skip_ranges.push(json_to_skip_range(numbers, key)?);
+                                    },
+                                    _ => {
+                                        return Err(Error::JsonTypeError {
+                                            key: Some(key.to_string()),
+                                            expected: JsonType::Array(Box::new(JsonType::Number)),
+                                            got: get_type(range),
+                                        });
+                                    },
+                                }
                             }
+
+                            result.set_skip_range(SkipValue::Manual(check_skip_ranges(skip_ranges)?));
                         },
                         _ => {
                             return Err(Error::JsonTypeError {
@@ -411,6 +634,9 @@ impl Graph {
                             });
                         },
                     },
+                    "skip_policy" => {
+                        result.set_skip_policy(json_to_skip_policy(value)?);
+                    },
                     "y_label_prefix" => match value.as_str() {
                         Some(p) => {
                             formatter.prefix = p.to_string();
@@ -497,41 +723,118 @@ impl Graph {
                             });
                         },
                     },
-                    "horizontal_break" => match value {
-                        JsonValue::Array(numbers) => if numbers.len() == 2 {
-                            let start = match numbers[0].as_usize() {
-                                Some(n) => n,
-                                _ => {
-                                    return Err(Error::JsonTypeError {
-                                        key: Some(key.to_string()),
-                                        expected: JsonType::Integer,
-                                        got: get_type(&numbers[0]),
-                                    });
-                                },
-                            };
-                            let end = match numbers[1].as_usize() {
-                                Some(n) => n,
-                                _ => {
-                                    return Err(Error::JsonTypeError {
-                                        key: Some(key.to_string()),
-                                        expected: JsonType::Integer,
-                                        got: get_type(&numbers[1]),
-                                    });
-                                },
-                            };
-
-                            result.set_horizontal_break(start, end);
-                        } else {
-                            return Err(Error::JsonArrayLengthError {
+                    "interval_highlight" => match value.as_str() {
+                        Some(highlight) => {
+                            result.set_interval_highlight(
+                                IntervalHighlight::from_str(highlight).map_err(
+                                    Error::InvalidIntervalHighlight
+                                )?
+                            );
+                        },
+                        _ => {
+                            return Err(Error::JsonTypeError {
                                 key: Some(key.to_string()),
-                                expected: 2,
-                                got: numbers.len(),
+                                expected: JsonType::String,
+                                got: get_type(value),
+                            });
+                        },
+                    },
+                    "interval_highlight_color" => match value.as_str() {
+                        Some(color) => {
+                            result.set_interval_highlight_color(
+                                Some(Color::from_str(color).map_err(
+                                    Error::InvalidColorName
+                                )?)
+                            );
+                        },
+                        _ => {
+                            return Err(Error::JsonTypeError {
+                                key: Some(key.to_string()),
+                                expected: JsonType::String,
+                                got: get_type(value),
+                            });
+                        },
+                    },
+                    "legend" => match value.as_str() {
+                        Some(position) => {
+                            result.set_legend(Some(Legend::new(
+                                LegendPosition::from_str(position).map_err(
+                                    Error::InvalidLegendPosition
+                                )?
+                            )));
+                        },
+                        _ => {
+                            return Err(Error::JsonTypeError {
+                                key: Some(key.to_string()),
+                                expected: JsonType::String,
+                                got: get_type(value),
+                            });
+                        },
+                    },
+                    "gridlines" => match value.as_str() {
+                        Some(gridlines) => {
+                            result.set_gridlines(
+                                Gridlines::from_str(gridlines).map_err(
+                                    Error::InvalidGridlines
+                                )?
+                            );
+                        },
+                        _ => {
+                            return Err(Error::JsonTypeError {
+                                key: Some(key.to_string()),
+                                expected: JsonType::String,
+                                got: get_type(value),
+                            });
+                        },
+                    },
+                    "gridline_color" => match value.as_str() {
+                        Some(color) => {
+                            result.set_gridline_color(
+                                Some(Color::from_str(color).map_err(
+                                    Error::InvalidColorName
+                                )?)
+                            );
+                        },
+                        _ => {
+                            return Err(Error::JsonTypeError {
+                                key: Some(key.to_string()),
+                                expected: JsonType::String,
+                                got: get_type(value),
                             });
                         },
+                    },
+                    // It was the columns of the plot, and `horizontal_breaks` are the indices of the data.
+                    // It's an error, so that the old documents don't silently draw different breaks.
+                    "horizontal_break" => {
+                        return Err(Error::RemovedKey {
+                            key: key.to_string(),
+                            replacement: String::from("horizontal_breaks"),
+                        });
+                    },
+                    "horizontal_breaks" => match value {
+                        // [[Integer, Integer]]
+                        JsonValue::Array(breaks) => {
+                            for horizontal_break in breaks.iter() {
+                                match horizontal_break {
+                                    JsonValue::Array(numbers) => {
+                                        let (start, end) = json_to_horizontal_break(numbers, key)?;
+
+                                        result.add_horizontal_break(start, end);
+                                    },
+                                    _ => {
+                                        return Err(Error::JsonTypeError {
+                                            key: Some(key.to_string()),
+                                            expected: JsonType::Array(Box::new(JsonType::Integer)),
+                                            got: get_type(horizontal_break),
+                                        });
+                                    },
+                                }
+                            }
+                        },
                         _ => {
                             return Err(Error::JsonTypeError {
                                 key: Some(key.to_string()),
-                                expected: JsonType::Array(Box::new(JsonType::Integer)),
+                                expected: JsonType::Array(Box::new(JsonType::Array(Box::new(JsonType::Integer)))),
                                 got: get_type(value),
                             });
                         },
@@ -590,3 +893,161 @@ fn json_to_ratio(n: &JsonValue) -> Result<Ratio, Error> {
         })
     }
 }
+
+// [from, to] of `skip_range`
+fn json_to_skip_range(numbers: &[JsonValue], key: &str) -> Result<(Ratio, Ratio), Error> {
+    if numbers.len() == 2 {
+        Ok((json_to_ratio(&numbers[0])?, json_to_ratio(&numbers[1])?))
+    }
+
+    else {
+        Err(Error::JsonArrayLengthError {
+            key: Some(key.to_string()),
+            expected: 2,
+            got: numbers.len(),
+        })
+    }
+}
+
+// It sorts `ranges`, and checks them like `SkipValue::manual_ranges`: `from <= to` for each range, and they don't overlap.
+fn check_skip_ranges(mut ranges: Vec<(Ratio, Ratio)>) -> Result<Vec<(Ratio, Ratio)>, Error> {
+    ranges.sort_by(|(from1, _), (from2, _)| from1.cmp(from2));
+
+    for (index, (from, to)) in ranges.iter().enumerate() {
+        if from.gt_rat(to) || index > 0 && ranges[index - 1].1.geq_rat(from) {
+            return Err(Error::InvalidSkipRange(format!("[{from}, {to}]")));
+        }
+    }
+
+    Ok(ranges)
+}
+
+// [start, end] of `horizontal_break`
+fn json_to_horizontal_break(numbers: &[JsonValue], key: &str) -> Result<(usize, usize), Error> {
+    if numbers.len() != 2 {
+        return Err(Error::JsonArrayLengthError {
+            key: Some(key.to_string()),
+            expected: 2,
+            got: numbers.len(),
+        });
+    }
+
+    let mut result = [0; 2];
+
+    for (index, n) in numbers.iter().enumerate() {
+        result[index] = match n.as_usize() {
+            Some(n) => n,
+            _ => {
+                return Err(Error::JsonTypeError {
+                    key: Some(key.to_string()),
+                    expected: JsonType::Integer,
+                    got: get_type(n),
+                });
+            },
+        };
+    }
+
+    Ok((result[0], result[1]))
+}
+
+fn json_to_skip_policy(value: &JsonValue) -> Result<SkipPolicy, Error> {
+    if !value.is_object() {
+        return Err(Error::JsonTypeError {
+            key: Some(String::from("skip_policy")),
+            expected: JsonType::Object,
+            got: get_type(value),
+        });
+    }
+
+    let mut result = SkipPolicy::default();
+
+    for (key, value) in value.entries() {
+        let usize_value = || match value.as_u32() {
+            Some(n) => Ok(n as usize),
+            _ => Err(Error::JsonTypeError {
+                key: Some(key.to_string()),
+                expected: JsonType::Integer,
+                got: get_type(value),
+            }),
+        };
+
+        match key {
+            "min_plot_height" => {
+                result.set_min_plot_height(usize_value()?);
+            },
+            "min_gap_ratio" => {
+                let min_gap_ratio = json_to_ratio(value)?;
+
+                if !min_gap_ratio.gt_rat(&Ratio::zero()) {
+                    return Err(Error::InvalidSkipPolicy { key: key.to_string(), value: min_gap_ratio.to_string() });
+                }
+
+                result.set_min_gap_ratio(min_gap_ratio);
+            },
+            "max_ranges" => {
+                result.set_max_ranges(if value.is_null() { None } else { Some(usize_value()?) });
+            },
+            "padding" => {
+                let padding = json_to_ratio(value)?;
+
+                if !padding.gt_rat(&Ratio::zero()) {
+                    return Err(Error::InvalidSkipPolicy { key: key.to_string(), value: padding.to_string() });
+                }
+
+                result.set_padding(padding);
+            },
+            "min_subplot_height" => {
+                result.set_min_subplot_height(usize_value()?);
+            },
+            "height_allocation" => match value.as_str() {
+                Some(height_allocation) => {
+                    result.set_height_allocation(
+                        HeightAllocation::from_str(height_allocation).map_err(
+                            Error::InvalidHeightAllocation
+                        )?
+                    );
+                },
+                _ => {
+                    return Err(Error::JsonTypeError {
+                        key: Some(key.to_string()),
+                        expected: JsonType::String,
+                        got: get_type(value),
+                    });
+                },
+            },
+            "never_skip" => match value {
+                JsonValue::Array(numbers) if numbers.len() == 2 => {
+                    let mut range = [None, None];
+
+                    for (index, n) in numbers.iter().enumerate() {
+                        if !n.is_null() {
+                            range[index] = Some(json_to_ratio(n)?);
+                        }
+                    }
+
+                    let [from, to] = range;
+                    result.set_never_skip(from, to);
+                },
+                JsonValue::Array(numbers) => {
+                    return Err(Error::JsonArrayLengthError {
+                        key: Some(key.to_string()),
+                        expected: 2,
+                        got: numbers.len(),
+                    });
+                },
+                _ => {
+                    return Err(Error::JsonTypeError {
+                        key: Some(key.to_string()),
+                        expected: JsonType::Array(Box::new(JsonType::Number)),
+                        got: get_type(value),
+                    });
+                },
+            },
+            _ => {
+                return Err(Error::UnknownKey(key.to_string()));
+            },
+        }
+    }
+
+    Ok(result)
+}
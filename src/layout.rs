@@ -0,0 +1,154 @@
+use hmath::Ratio;
+use std::ops::Range;
+
+/// A rectangle in the output, in characters. (0, 0) is the top-left corner of the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        self.x <= x && x < self.x + self.width && self.y <= y && y < self.y + self.height
+    }
+}
+
+/// The y axis of a subplot. If the graph skips ranges of the y axis, each subplot has its own scale.
+#[derive(Clone, Debug, PartialEq)]
+pub struct YScale {
+    /// rows of the subplot in the output
+    pub rows: Range<usize>,
+
+    /// The range of the subplot, after it's prettified. They're the values of the bottom and the top of `rows`, like the y labels.
+    pub y_min: Ratio,
+    pub y_max: Ratio,
+}
+
+/// Where things are in the output of `Graph::draw`, and how the data is mapped to the characters.
+/// See `Graph::layout` and `Graph::render_with_metadata`.
+///
+/// All the coordinates are in characters, and (0, 0) is the top-left corner of the output,
+/// including the title and the paddings.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Layout {
+    /// size of the whole output
+    pub width: usize,
+    pub height: usize,
+
+    /// The plot area, without the axes and the labels. If the y axis is skipped, it includes the horizontal lines
+    /// between the subplots, and if there're horizontal breaks, it includes the break lines.
+    pub plot_area: Rect,
+
+    /// From the bottom to the top. It's empty if the graph doesn't have a y axis (2-dimensional graphs, heatmaps and horizontal box plots).
+    pub y_scales: Vec<YScale>,
+
+    /// The ranges of the y axis that are not drawn, from the bottom to the top. See `Graph::get_skipped_ranges`.
+    pub skipped_ranges: Vec<(Ratio, Ratio)>,
+
+    /// The range of the x axis of horizontal box plots, after it's prettified. It's `None` for the other graphs.
+    pub x_range: Option<(Ratio, Ratio)>,
+
+    /// `columns[i]` is the columns of the `i`th data in the output.
+    ///
+    /// - 1-dimensional data: the index of the data. If the data is longer than the plot, many data share a column.
+    /// - time series: the index of the resampled columns, which means `columns[i]` is the `i`th column of the plot.
+    /// - box plots: the index of the category
+    /// - 2-dimensional data and heatmaps: the x coordinate
+    ///
+    /// It's an empty range at the break line if the data is hidden by a horizontal break.
+    pub columns: Vec<Range<usize>>,
+
+    /// the columns of the horizontal break lines in the output
+    pub horizontal_breaks: Vec<Range<usize>>,
+
+    /// Where the legend (or the scale of a heatmap) is. It's `None` if the graph doesn't have one. See `Graph::set_legend`.
+    pub legend: Option<Rect>,
+}
+
+impl Layout {
+    /// The row of `value` in the output, where the top of its bar would be drawn. It's `None` if the graph doesn't have a y axis.
+    ///
+    /// A value in a skipped range is on the horizontal line between the subplots, and a value out of
+    /// the y axis is on the top (or the bottom) row of the plot.
+    pub fn get_row(&self, value: &Ratio) -> Option<usize> {
+        let (bottom, top) = (self.y_scales.first()?, self.y_scales.last()?);
+
+        if value.gt_rat(&top.y_max) {
+            return Some(top.rows.start);
+        }
+
+        if value.lt_rat(&bottom.y_min) {
+            return Some(bottom.rows.end - 1);
+        }
+
+        for y_scale in self.y_scales.iter() {
+            if value.leq_rat(&y_scale.y_max) {
+                if value.lt_rat(&y_scale.y_min) {
+                    // the horizontal line is right below the subplot
+                    return Some(y_scale.rows.end);
+                }
+
+                let height = y_scale.rows.len();
+
+                // truncate((y_max - value) / (y_max - y_min) * height), like `plot_1d`
+                let row = y_scale.y_max.sub_rat(value).div_rat(&y_scale.y_max.sub_rat(&y_scale.y_min)).mul_i32(height as i32).truncate_bi().to_i32().unwrap_or(0);
+
+                return Some(y_scale.rows.start + (row.max(0) as usize).min(height.saturating_sub(1)));
+            }
+        }
+
+        None
+    }
+
+    // It moves everything by (x, y).
+    pub(crate) fn shift(&mut self, x: usize, y: usize) {
+        self.plot_area.x += x;
+        self.plot_area.y += y;
+
+        if let Some(legend) = &mut self.legend {
+            legend.x += x;
+            legend.y += y;
+        }
+
+        for y_scale in self.y_scales.iter_mut() {
+            y_scale.rows = (y_scale.rows.start + y)..(y_scale.rows.end + y);
+        }
+
+        for range in self.columns.iter_mut().chain(self.horizontal_breaks.iter_mut()) {
+            *range = (range.start + x)..(range.end + x);
+        }
+    }
+}
+
+/// What's at a character of the output. See `Graph::pick`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Pick {
+    /// a data of 1-dimensional data, time series or box plots
+    Data {
+        /// the same index as `Layout::columns`
+        index: usize,
+        label: String,
+
+        /// The value that's drawn. If the data is longer than the plot, it's the downsampled one.
+        /// For box plots, it's the median, and it's `None` if the category has no samples.
+        value: Option<Ratio>,
+
+        /// The value of the y axis at the row (the x axis for horizontal box plots), like the y labels.
+        /// It's `None` if the row is on a horizontal line between the subplots.
+        axis_value: Option<Ratio>,
+    },
+
+    /// a cell of 2-dimensional data or heatmaps
+    Cell {
+        x: usize,
+        y: usize,
+        x_label: Option<String>,
+        y_label: Option<String>,
+
+        /// the value of the cell, only for heatmaps
+        value: Option<Ratio>,
+    },
+}
@@ -0,0 +1,151 @@
+use crate::alignment::Alignment;
+use crate::charset::Glyphs;
+use crate::color::{Color, ColorMode};
+use crate::lines::Lines;
+use std::str::FromStr;
+
+/// It explains the glyphs and the colors of a graph. See `Graph::set_legend`.
+///
+/// The entries are populated from the graph: the data (with the primary color), the outliers of box plots,
+/// the skipped ranges, the horizontal breaks, the highlights of the labeled intervals, the annotations and the scale of heatmaps.
+/// The entries added by `add_entry` come after them.
+///
+/// ```nohighlight
+/// let mut legend = Legend::new(LegendPosition::Bottom);
+/// legend.set_data_label("cpu").add_entry('-', Some(Color::Red), "limit");
+/// graph.set_legend(Some(legend));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Legend {
+    pub(crate) position: LegendPosition,
+    pub(crate) entries: Vec<LegendEntry>,
+    pub(crate) auto_entries: bool,
+
+    // the label of the data, the y axis label if it's not set
+    pub(crate) data_label: Option<String>,
+}
+
+impl Legend {
+    pub fn new(position: LegendPosition) -> Self {
+        Legend {
+            position,
+            entries: vec![],
+            auto_entries: true,
+            data_label: None,
+        }
+    }
+
+    pub fn set_position(&mut self, position: LegendPosition) -> &mut Self {
+        self.position = position;
+
+        self
+    }
+
+    pub fn add_entry<T: ToString>(&mut self, glyph: char, color: Option<Color>, label: T) -> &mut Self {
+        self.entries.push(LegendEntry {
+            glyph,
+            color,
+            label: label.to_string(),
+        });
+
+        self
+    }
+
+    /// If it's false, only the entries added by `add_entry` are drawn. It's default to true.
+    pub fn set_auto_entries(&mut self, auto_entries: bool) -> &mut Self {
+        self.auto_entries = auto_entries;
+
+        self
+    }
+
+    /// The label of the entry of the data. If it's not set, it's the y axis label, or "data" if the graph doesn't have one.
+    pub fn set_data_label<T: ToString>(&mut self, data_label: T) -> &mut Self {
+        self.data_label = Some(data_label.to_string());
+
+        self
+    }
+}
+
+impl Default for Legend {
+    fn default() -> Self {
+        Legend::new(LegendPosition::default())
+    }
+}
+
+/// A line of a `Legend`: a glyph with its color, and what it means.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LegendEntry {
+    pub glyph: char,
+    pub color: Option<Color>,
+    pub label: String,
+}
+
+/// Where a `Legend` is drawn.
+///
+/// It's default to `LegendPosition::Right`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LegendPosition {
+    /// on the right side of the plot, an entry per line
+    #[default]
+    Right,
+
+    /// below the x labels, all the entries in a line
+    Bottom,
+
+    /// In a corner of the plot area, with a border. It covers the plot, but it doesn't make the graph larger.
+    /// If it's larger than the plot area, it's cropped.
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl FromStr for LegendPosition {
+    type Err = String;
+
+    /// returns Err(s) if it fails
+    fn from_str(s: &str) -> Result<LegendPosition, String> {
+        match s.replace(" ", "").replace("_", "").replace("-", "").to_ascii_lowercase() {
+            s if s == "right" => Ok(LegendPosition::Right),
+            s if s == "bottom" => Ok(LegendPosition::Bottom),
+            s if s == "topleft" => Ok(LegendPosition::TopLeft),
+            s if s == "topright" => Ok(LegendPosition::TopRight),
+            s if s == "bottomleft" => Ok(LegendPosition::BottomLeft),
+            s if s == "bottomright" => Ok(LegendPosition::BottomRight),
+            _ => Err(s.to_string()),
+        }
+    }
+}
+
+pub fn draw_legend(entries: &[LegendEntry], position: LegendPosition, glyphs: &Glyphs) -> Lines {
+    let entries = entries.iter().map(draw_entry);
+
+    match position {
+        LegendPosition::Right => entries.fold(
+            Lines::empty(),
+            |result, entry| result.merge_vertically(&entry, Alignment::First),
+        ),
+        LegendPosition::Bottom => entries.enumerate().fold(
+            Lines::empty(),
+            |result, (index, entry)| if index == 0 {
+                entry
+            } else {
+                result.merge_horizontally(&Lines::new(3, 1), Alignment::First).merge_horizontally(&entry, Alignment::First)
+            },
+        ),
+        _ => entries.fold(
+            Lines::empty(),
+            |result, entry| result.merge_vertically(&entry, Alignment::First),
+        ).add_padding([0, 0, 1, 1]).add_border([true; 4], glyphs),
+    }
+}
+
+// `{glyph} {label}`, in a line
+fn draw_entry(entry: &LegendEntry) -> Lines {
+    let mut sample = Lines::from_string(&entry.glyph.to_string(), Alignment::First, &ColorMode::None);
+    sample.set_color_all(entry.color.clone());
+
+    let label = Lines::from_string(&format!(" {}", entry.label.replace('\n', " ")), Alignment::First, &ColorMode::None);
+
+    sample.merge_horizontally(&label, Alignment::First)
+}
@@ -2,15 +2,31 @@
 //! But `set_y_max("3.2")` uses internal ratio type which can represent any rational number perfectly.
 
 mod alignment;
+mod annotation;
+mod canvas;
+mod charset;
 mod color;
+mod dashboard;
+mod downsampling;
 mod format;
 mod graph;
+mod gridlines;
 mod interval;
+mod layout;
+mod legend;
 mod lines;
+mod live_graph;
+mod numeric;
+mod orientation;
+mod proportion;
+mod skip_policy;
 mod skip_value;
-mod table;
+mod sparkline;
+mod time_series;
 mod utils;
 
+// TODO: `table.rs` is WIP, and it's not compiled until it's finished
+
 #[cfg(feature = "json")]
 mod error;
 
@@ -18,17 +34,34 @@ mod error;
 mod json;
 
 pub use alignment::Alignment;
+pub use annotation::{Annotation, AnnotationStyle};
+pub use canvas::{Canvas, Resolution};
+pub use charset::{Charset, Glyphs};
 pub use color::{Color, ColorMode};
+pub use dashboard::{Dashboard, Panel};
+pub use downsampling::Downsampling;
 pub use format::{
     DefaultFormatter,
     NumberFormatter,
 };
 pub use graph::{
     Graph,
+    SharedAxes,
     merge_horiz,
     merge_vert,
+    share_axes,
 };
+pub use gridlines::Gridlines;
+pub use interval::IntervalHighlight;
+pub use layout::{Layout, Pick, Rect, YScale};
+pub use legend::{Legend, LegendEntry, LegendPosition};
+pub use lines::Grapheme;
+pub use live_graph::{LiveGraph, LiveGraphStyle};
+pub use orientation::Orientation;
+pub use proportion::{ProportionChart, ProportionStyle};
+pub use skip_policy::{HeightAllocation, SkipPolicy};
 pub use skip_value::SkipValue;
+pub use sparkline::Sparkline;
 
 #[cfg(feature = "json")]
 pub use error::{Error, JsonType};
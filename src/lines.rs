@@ -1,11 +1,55 @@
 use crate::alignment::Alignment;
+use crate::charset::Glyphs;
 use crate::color::{Color, ColorMode};
-use crate::utils::into_v16;
+use crate::utils::grapheme_width;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A cell of `Lines`.
+///
+/// A double-width grapheme (CJK, emoji) occupies 2 cells: the grapheme itself and a `WideTail` right after it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Grapheme {
+    Char(char),
+
+    /// A grapheme cluster with multiple code points (combining marks, ZWJ sequences...).
+    /// If the string was colored by this library, the ANSI escape sequences are also stored here,
+    /// so that they're preserved when the string is rendered again.
+    Cluster(Box<str>),
+
+    /// It's not rendered.
+    WideTail,
+}
+
+impl Grapheme {
+    pub fn width(&self) -> usize {
+        match self {
+            Grapheme::Char(c) => grapheme_width(c.encode_utf8(&mut [0; 4])),
+            Grapheme::Cluster(s) => grapheme_width(&strip_escape_sequences(s)),
+            Grapheme::WideTail => 0,
+        }
+    }
+
+    fn push_to(&self, buffer: &mut String) {
+        match self {
+            Grapheme::Char(c) => { buffer.push(*c); },
+            Grapheme::Cluster(s) => { buffer.push_str(s); },
+            Grapheme::WideTail => {},
+        }
+    }
+}
 
+const SPACE: Grapheme = Grapheme::Char(' ');
+
+/// A rectangular buffer of characters and their colors.
+///
+/// The cells are stored in a flat buffer, row by row. `blit_mut` and `blit_region_mut` modify the buffer in place,
+/// and the methods that return a new `Lines` (`merge_vertically`, `add_padding`...) decide the size of the result first,
+/// allocate it once, and copy the inputs into it.
 #[derive(Clone, Debug)]
 pub struct Lines {
-    lines: Vec<Vec<u16>>,
-    colors: Vec<Vec<Option<Color>>>,
+    // `cells[y * width + x]`
+    cells: Vec<Grapheme>,
+    colors: Vec<Option<Color>>,
     width: usize,
     height: usize,
 }
@@ -13,15 +57,15 @@ pub struct Lines {
 impl Lines {
     pub fn new(width: usize, height: usize) -> Self {
         Lines {
-            lines: vec![vec![' ' as u16; width]; height],
-            colors: vec![vec![None; width]; height],
+            cells: vec![SPACE; width * height],
+            colors: vec![None; width * height],
             width, height,
         }
     }
 
     pub fn empty() -> Self {
         Lines {
-            lines: vec![],
+            cells: vec![],
             colors: vec![],
             width: 0,
             height: 0,
@@ -36,339 +80,176 @@ impl Lines {
         self.height
     }
 
-    pub fn get(&self, x: usize, y: usize) -> u16 {
-        self.lines[y][x]
+    pub fn get(&self, x: usize, y: usize) -> &Grapheme {
+        &self.cells[self.index(x, y)]
     }
 
     pub fn get_color(&self, x: usize, y: usize) -> Option<Color> {
-        self.colors[y][x].clone()
+        self.colors[self.index(x, y)].clone()
     }
 
-    pub fn set(&mut self, x: usize, y: usize, c: u16) {
-        self.lines[y][x] = c;
+    /// If `c` is a double-width character, it also occupies `(x + 1, y)`.
+    pub fn set(&mut self, x: usize, y: usize, c: char) {
+        let width = Grapheme::Char(c).width();
+        self.set_grapheme(x, y, Grapheme::Char(c), width);
     }
 
-    pub fn set_color(&mut self, x: usize, y: usize, color: Option<Color>) {
-        self.colors[y][x] = color;
-    }
+    fn index(&self, x: usize, y: usize) -> usize {
+        assert!(x < self.width && y < self.height, "({x}, {y}) is out of a {}x{} `Lines`", self.width, self.height);
 
-    #[must_use = "method returns a new number and does not mutate the original value"]
-    pub fn crop(&self, x: usize, y: usize, w: usize, h: usize) -> Lines {
-        let w = w.min(self.width - x);
-        let h = h.min(self.height - y);
+        y * self.width + x
+    }
 
-        let new_lines: Vec<Vec<u16>> = (y..(y + h)).map(|line_no| self.lines[line_no][x..(x + w)].to_vec()).collect();
-        let new_colors: Vec<Vec<Option<Color>>> = (y..(y + h)).map(|line_no| self.colors[line_no][x..(x + w)].to_vec()).collect();
+    fn row(&self, y: usize) -> &[Grapheme] {
+        &self.cells[(y * self.width)..((y + 1) * self.width)]
+    }
 
-        Lines {
-            lines: new_lines,
-            colors: new_colors,
-            width: w,
-            height: h,
-        }
+    fn row_colors(&self, y: usize) -> &[Option<Color>] {
+        &self.colors[(y * self.width)..((y + 1) * self.width)]
     }
 
-    // if you want to blit a fraction of `other`, crop it first
-    #[must_use = "method returns a new number and does not mutate the original value"]
-    pub fn blit(&self, other: &Lines, x: usize, y: usize, transparent_char: Option<char>) -> Lines {
-        let mut result = self.clone();
+    // It doesn't leave a half of a double-width grapheme.
+    fn set_grapheme(&mut self, x: usize, y: usize, g: Grapheme, width: usize) {
+        let start = y * self.width;
+        let line = &mut self.cells[start..(start + self.width)];
 
-        if x >= self.width || y >= self.height {
-            return result;
+        if line[x] == Grapheme::WideTail && x > 0 {
+            line[x - 1] = SPACE;
         }
 
-        let transparent_char = transparent_char.map(|c| c as u16);
-
-        for x_ in x..self.width.min(x + other.width) {
-            for y_ in y..self.height.min(y + other.height) {
-                let ch = other.get(x_ - x, y_ - y);
-                let color = other.get_color(x_ - x, y_ - y);
+        if x + 1 < line.len() && line[x + 1] == Grapheme::WideTail {
+            line[x + 1] = SPACE;
+        }
 
-                if Some(ch) == transparent_char {
-                    continue;
+        if width == 2 {
+            if x + 1 < line.len() {
+                if x + 2 < line.len() && line[x + 2] == Grapheme::WideTail {
+                    line[x + 2] = SPACE;
                 }
 
-                result.set(x_, y_, ch);
-                result.set_color(x_, y_, color);
+                line[x] = g;
+                line[x + 1] = Grapheme::WideTail;
+            }
+
+            // there's no room for the grapheme
+            else {
+                line[x] = SPACE;
             }
         }
 
-        result
+        else {
+            line[x] = g;
+        }
     }
 
-    #[must_use = "method returns a new number and does not mutate the original value"]
-    pub fn merge_vertically(&self, other: &Lines, alignment: Alignment) -> Lines {
-        let (padding1, padding2) = if self.width >= other.width {
-            (0, 0)
-        } else {
-            match alignment {
-                Alignment::Center => (
-                    (other.width - self.width) / 2 + (other.width - self.width) % 2,
-                    (other.width - self.width) / 2,
-                ),
-                Alignment::First => (
-                    0,
-                    other.width - self.width,
-                ),
-                Alignment::Last => (
-                    other.width - self.width,
-                    0,
-                ),
-                _ => todo!(),
-            }
-        };
-
-        let (padding3, padding4) = if other.width >= self.width {
-            (0, 0)
-        } else {
-            match alignment {
-                Alignment::Center => (
-                    (self.width - other.width) / 2 + (self.width - other.width) % 2,
-                    (self.width - other.width) / 2,
-                ),
-                Alignment::First => (
-                    0,
-                    self.width - other.width,
-                ),
-                Alignment::Last => (
-                    self.width - other.width,
-                    0
-                ),
-                _ => todo!(),
-            }
-        };
+    pub fn set_color(&mut self, x: usize, y: usize, color: Option<Color>) {
+        let index = self.index(x, y);
+        self.colors[index] = color;
+    }
 
-        let new_lines = vec![
-            self.lines.iter().map(
-                |line| vec![
-                    vec![' ' as u16; padding1],
-                    line.to_vec(),
-                    vec![' ' as u16; padding2],
-                ].concat()
-            ).collect::<Vec<Vec<u16>>>(),
-            other.lines.iter().map(
-                |line| vec![
-                    vec![' ' as u16; padding3],
-                    line.to_vec(),
-                    vec![' ' as u16; padding4],
-                ].concat()
-            ).collect(),
-        ].concat();
-
-        let new_colors = vec![
-            self.colors.iter().map(
-                |color| vec![
-                    vec![None; padding1],
-                    color.to_vec(),
-                    vec![None; padding2],
-                ].concat()
-            ).collect::<Vec<Vec<Option<Color>>>>(),
-            other.colors.iter().map(
-                |color| vec![
-                    vec![None; padding3],
-                    color.to_vec(),
-                    vec![None; padding4],
-                ].concat()
-            ).collect(),
-        ].concat();
+    // It copies `other` to (x, y) as it is, without looking at the graphemes. `other` must fit in `self`.
+    fn copy_from(&mut self, other: &Lines, x: usize, y: usize) {
+        for y_ in 0..other.height {
+            let start = (y + y_) * self.width + x;
 
-        Lines {
-            lines: new_lines,
-            colors: new_colors,
-            width: self.width.max(other.width),
-            height: self.height + other.height,
+            self.cells[start..(start + other.width)].clone_from_slice(other.row(y_));
+            self.colors[start..(start + other.width)].clone_from_slice(other.row_colors(y_));
         }
     }
 
-    #[must_use = "method returns a new number and does not mutate the original value"]
-    pub fn merge_horizontally(&self, other: &Lines, alignment: Alignment) -> Lines {
-        if self.height < other.height {
-            let (padding1, padding2) = match alignment {
-                Alignment::First => (0, other.height - self.height),
-                Alignment::Last => (other.height - self.height, 0),
-                Alignment::Center => (
-                    (other.height - self.height) / 2 + (other.height - self.height) % 2,
-                    (other.height - self.height) / 2,
-                ),
-                _ => todo!(),
-            };
-            let mut new_lines = Vec::with_capacity(other.height);
-            let mut new_colors = Vec::with_capacity(other.height);
-            let mut index = 0;
-
-            for _ in 0..padding1 {
-                new_lines.push(vec![
-                    vec![' ' as u16; self.width],
-                    other.lines[index].clone(),
-                ].concat());
-                new_colors.push(vec![
-                    vec![None; self.width],
-                    other.colors[index].clone(),
-                ].concat());
-
-                index += 1;
-            }
-
-            for _ in 0..self.height {
-                new_lines.push(vec![
-                    self.lines[index - padding1].clone(),
-                    other.lines[index].clone(),
-                ].concat());
-                new_colors.push(vec![
-                    self.colors[index - padding1].clone(),
-                    other.colors[index].clone(),
-                ].concat());
-
-                index += 1;
-            }
+    /// It draws `other` on `self` at (x, y). The cells of `other` that are `transparent_char` are not drawn.
+    /// If you want to draw a fraction of `other`, use `blit_region_mut`.
+    pub fn blit_mut(&mut self, other: &Lines, x: usize, y: usize, transparent_char: Option<char>) {
+        self.blit_region_mut(other, (0, 0, other.width, other.height), x, y, transparent_char);
+    }
 
-            for _ in 0..padding2 {
-                new_lines.push(vec![
-                    vec![' ' as u16; self.width],
-                    other.lines[index].clone(),
-                ].concat());
-                new_colors.push(vec![
-                    vec![None; self.width],
-                    other.colors[index].clone(),
-                ].concat());
-
-                index += 1;
-            }
+    /// The `w`x`h` region at (x, y), cropped to fit in `self`. It used to copy the region to a new `Lines`,
+    /// but now it only returns (x, y, w, h), so that `blit_region_mut` can draw the region without copying it.
+    pub fn crop(&self, x: usize, y: usize, w: usize, h: usize) -> (usize, usize, usize, usize) {
+        (x, y, w.min(self.width - x), h.min(self.height - y))
+    }
 
-            Lines {
-                lines: new_lines,
-                colors: new_colors,
-                width: self.width + other.width,
-                height: other.height,
-            }
+    /// It draws the `w`x`h` region of `other` at (src_x, src_y) on `self` at (x, y), without copying `other`.
+    /// The region is cropped to fit in `other` (see `crop`).
+    pub fn blit_region_mut(
+        &mut self,
+        other: &Lines,
+        (src_x, src_y, w, h): (usize, usize, usize, usize),
+        x: usize,
+        y: usize,
+        transparent_char: Option<char>,
+    ) {
+        if x >= self.width || y >= self.height {
+            return;
         }
 
-        else if self.height == other.height {
-            let new_lines = (0..self.height).map(
-                |i| vec![
-                    self.lines[i].clone(),
-                    other.lines[i].clone(),
-                ].concat()
-            ).collect();
-            let new_colors = (0..self.height).map(
-                |i| vec![
-                    self.colors[i].clone(),
-                    other.colors[i].clone(),
-                ].concat()
-            ).collect();
-
-            Lines {
-                lines: new_lines,
-                colors: new_colors,
-                width: self.width + other.width,
-                height: self.height,
-            }
-        }
+        let (src_x, src_y, w, h) = other.crop(src_x, src_y, w, h);
+        let transparent_char = transparent_char.map(Grapheme::Char);
 
-        else {
-            let (padding1, padding2) = match alignment {
-                Alignment::First => (0, self.height - other.height),
-                Alignment::Last => (self.height - other.height, 0),
-                Alignment::Center => (
-                    (self.height - other.height) / 2 + (self.height - other.height) % 2,
-                    (self.height - other.height) / 2,
-                ),
-                _ => todo!(),
-            };
-            let mut new_lines = Vec::with_capacity(self.height);
-            let mut new_colors = Vec::with_capacity(self.height);
-            let mut index = 0;
-
-            for _ in 0..padding1 {
-                new_lines.push(vec![
-                    self.lines[index].clone(),
-                    vec![' ' as u16; other.width],
-                ].concat());
-                new_colors.push(vec![
-                    self.colors[index].clone(),
-                    vec![None; other.width],
-                ].concat());
-
-                index += 1;
-            }
+        for y_ in y..self.height.min(y + h) {
+            for x_ in x..self.width.min(x + w) {
+                let (other_x, other_y) = (src_x + x_ - x, src_y + y_ - y);
+                let mut ch = other.get(other_x, other_y);
+                let color = other.get_color(other_x, other_y);
 
-            for _ in 0..other.height {
-                new_lines.push(vec![
-                    self.lines[index].clone(),
-                    other.lines[index - padding1].clone(),
-                ].concat());
-                new_colors.push(vec![
-                    self.colors[index].clone(),
-                    other.colors[index - padding1].clone(),
-                ].concat());
-
-                index += 1;
-            }
+                // a double-width grapheme cannot be cut in half at the edges of the region
+                if (x_ == x && *ch == Grapheme::WideTail) || (x_ + 1 == x + w && ch.width() == 2) {
+                    ch = &SPACE;
+                }
 
-            for _ in 0..padding2 {
-                new_lines.push(vec![
-                    self.lines[index].clone(),
-                    vec![' ' as u16; other.width],
-                ].concat());
-                new_colors.push(vec![
-                    self.colors[index].clone(),
-                    vec![None; other.width],
-                ].concat());
-
-                index += 1;
-            }
+                if Some(ch) == transparent_char.as_ref() {
+                    continue;
+                }
+
+                // tails are written with their graphemes
+                if *ch == Grapheme::WideTail {
+                    self.set_color(x_, y_, color);
+                    continue;
+                }
 
-            Lines {
-                lines: new_lines,
-                colors: new_colors,
-                width: self.width + other.width,
-                height: self.height,
+                self.set_grapheme(x_, y_, ch.clone(), ch.width());
+                self.set_color(x_, y_, color);
             }
         }
     }
 
+    #[must_use = "method returns a new number and does not mutate the original value"]
+    pub fn merge_vertically(&self, other: &Lines, alignment: Alignment) -> Lines {
+        let width = self.width.max(other.width);
+        let mut result = Lines::new(width, self.height + other.height);
+
+        result.copy_from(self, get_paddings(width - self.width, alignment).0, 0);
+        result.copy_from(other, get_paddings(width - other.width, alignment).0, self.height);
+
+        result
+    }
+
+    #[must_use = "method returns a new number and does not mutate the original value"]
+    pub fn merge_horizontally(&self, other: &Lines, alignment: Alignment) -> Lines {
+        let height = self.height.max(other.height);
+        let mut result = Lines::new(self.width + other.width, height);
+
+        result.copy_from(self, 0, get_paddings(height - self.height, alignment).0);
+        result.copy_from(other, self.width, get_paddings(height - other.height, alignment).0);
+
+        result
+    }
+
     /// top, bottom, left, right
     #[must_use = "method returns a new number and does not mutate the original value"]
     pub fn add_padding(&self, paddings: [usize; 4]) -> Lines {
-        let new_width = self.width + paddings[2] + paddings[3];
-
-        let new_lines = vec![
-            vec![vec![' ' as u16; new_width]; paddings[0]],
-            self.lines.iter().map(
-                |line|
-                vec![
-                    vec![' ' as u16; paddings[2]],
-                    line.to_vec(),
-                    vec![' ' as u16; paddings[3]],
-                ].concat()
-            ).collect::<Vec<Vec<u16>>>(),
-            vec![vec![' ' as u16; new_width]; paddings[1]],
-        ].concat();
-
-        let new_colors = vec![
-            vec![vec![None; new_width]; paddings[0]],
-            self.colors.iter().map(
-                |color|
-                vec![
-                    vec![None; paddings[2]],
-                    color.to_vec(),
-                    vec![None; paddings[3]],
-                ].concat()
-            ).collect::<Vec<Vec<Option<Color>>>>(),
-            vec![vec![None; new_width]; paddings[1]],
-        ].concat();
+        let mut result = Lines::new(
+            self.width + paddings[2] + paddings[3],
+            self.height + paddings[0] + paddings[1],
+        );
+        result.copy_from(self, paddings[2], paddings[0]);
 
-        Lines {
-            lines: new_lines,
-            colors: new_colors,
-            width: new_width,
-            height: self.height + paddings[0] + paddings[1],
-        }
+        result
     }
 
     /// top, bottom, left, right
     #[must_use = "method returns a new number and does not mutate the original value"]
-    pub fn add_border(&self, borders: [bool; 4]) -> Lines {
+    pub fn add_border(&self, borders: [bool; 4], glyphs: &Glyphs) -> Lines {
         let mut with_padding = self.add_padding([
             borders[0] as usize,
             borders[1] as usize,
@@ -378,42 +259,42 @@ impl Lines {
 
         if borders[0] {
             for x in 0..with_padding.width {
-                with_padding.set(x, 0, '─' as u16);
+                with_padding.set(x, 0, glyphs.horizontal);
             }
         }
 
         if borders[1] {
             for x in 0..with_padding.width {
-                with_padding.set(x, with_padding.height - 1, '─' as u16);
+                with_padding.set(x, with_padding.height - 1, glyphs.horizontal);
             }
         }
 
         if borders[2] {
             for y in 0..with_padding.height {
-                with_padding.set(0, y, '│' as u16);
+                with_padding.set(0, y, glyphs.vertical);
             }
         }
 
         if borders[3] {
             for y in 0..with_padding.height {
-                with_padding.set(with_padding.width - 1, y, '│' as u16);
+                with_padding.set(with_padding.width - 1, y, glyphs.vertical);
             }
         }
 
         if borders[0] && borders[2] {
-            with_padding.set(0, 0, '╭' as u16);
+            with_padding.set(0, 0, glyphs.corners[0]);
         }
 
         if borders[0] && borders[3] {
-            with_padding.set(with_padding.width - 1, 0, '╮' as u16);
+            with_padding.set(with_padding.width - 1, 0, glyphs.corners[1]);
         }
 
         if borders[1] && borders[2] {
-            with_padding.set(0, with_padding.height - 1, '╰' as u16);
+            with_padding.set(0, with_padding.height - 1, glyphs.corners[2]);
         }
 
         if borders[1] && borders[3] {
-            with_padding.set(with_padding.width - 1, with_padding.height - 1, '╯' as u16);
+            with_padding.set(with_padding.width - 1, with_padding.height - 1, glyphs.corners[3]);
         }
 
         with_padding
@@ -428,88 +309,70 @@ impl Lines {
     ) -> Self {
 
         // it seems like s.split() when s is empty returns a non-empty vector
-        if s.len() == 0 {
+        if s.is_empty() {
             return Lines::empty();
         }
 
         let mut max_width = 0;
-        let raw_lines: Vec<Vec<u16>> = s.split("\n").map(
+        let raw_lines: Vec<Vec<Grapheme>> = s.split("\n").map(
             |raw_line| {
-                let result = into_v16(&raw_line);
-                let line_len = count_chars(&result, color_mode);
+                let result = parse_line(raw_line, color_mode);
 
-                if line_len > max_width {
-                    max_width = line_len;
+                if result.len() > max_width {
+                    max_width = result.len();
                 }
 
                 result
             }
         ).collect();
-        let mut result = Vec::with_capacity(raw_lines.len());
-
-        for raw_line in raw_lines.into_iter() {
-            let line_len = count_chars(&raw_line, color_mode);
-            let (padding1, padding2) = match alignment {
-                Alignment::Center => (
-                    (max_width - line_len) / 2 + (max_width - line_len) % 2,
-                    (max_width - line_len) / 2,
-                ),
-                Alignment::First => (
-                    0,
-                    max_width - line_len,
-                ),
-                Alignment::Last => (
-                    max_width - line_len,
-                    0,
-                ),
-                _ => todo!(),
-            };
+        let mut result = Lines::new(max_width, raw_lines.len());
 
-            result.push(
-                vec![
-                    vec![' ' as u16; padding1],
-                    raw_line,
-                    vec![' ' as u16; padding2],
-                ].concat()
-            );
-        }
+        for (y, raw_line) in raw_lines.into_iter().enumerate() {
+            let start = y * max_width + get_paddings(max_width - raw_line.len(), alignment).0;
 
-        Lines {
-            width: if result.len() > 0 { count_chars(&result[0], color_mode) } else { 0 },
-            height: result.len(),
-            colors: result.iter().map(
-                |line| vec![None; count_chars(line, color_mode)]
-            ).collect(),
-            lines: result,
+            for (index, g) in raw_line.into_iter().enumerate() {
+                result.cells[start + index] = g;
+            }
         }
+
+        result
     }
 
     pub fn to_string(&self, color_mode: &ColorMode) -> String {
-        let string = self.lines.iter().map(|line| String::from_utf16_lossy(line)).collect::<Vec<String>>().join("\n");
+        let mut string = String::with_capacity((self.width + 1) * self.height);
+
+        // `ColorMode::apply_colors` needs a color for each char, including '\n'
+        let mut colors = vec![];
+
+        for index in 0..self.height {
+            let (line, line_colors) = (self.row(index), self.row_colors(index));
+
+            if index > 0 {
+                string.push('\n');
+                colors.push(None);
+            }
+
+            for (g, color) in line.iter().zip(line_colors.iter()) {
+                let prev_len = string.len();
+                g.push_to(&mut string);
+
+                for _ in string[prev_len..].chars() {
+                    colors.push(color.clone());
+                }
+            }
+        }
 
         if let ColorMode::None = color_mode {
             string
         }
 
         else {
-            color_mode.apply_colors(
-                string,
-                self.colors.join(&[None][..]),  // none color variants for '\n'
-            )
+            color_mode.apply_colors(string, colors)
         }
     }
 
     pub fn set_color_all(&mut self, color: Option<Color>) {
-        for cc in self.colors.iter_mut() {
-            for c in cc.iter_mut() {
-                *c = color.clone();
-            }
-        }
-    }
-
-    #[cfg(test)]
-    pub fn is_valid(&self) -> bool {
-        self.lines.len() == self.height && self.lines.iter().all(|line| line.len() == self.width)
+        self.colors.fill(color);
     }
 }
 
@@ -519,75 +382,106 @@ impl std::fmt::Display for Lines {
     }
 }
 
-enum AnsiEscapeParseState {
-    None,
-    Esc0,  // encountered `\x1b', expecting '['
-    Esc1,  // encountered '[', expecting '3'|'4'
-    Esc2,  // encountered '3'|'4', expecting 'm'
+// (before, after) of something that's `diff` cells smaller than the space it's aligned in
+fn get_paddings(diff: usize, alignment: Alignment) -> (usize, usize) {
+    if diff == 0 {
+        return (0, 0);
+    }
+
+    match alignment {
+        Alignment::Center => (diff / 2 + diff % 2, diff / 2),
+        Alignment::First => (0, diff),
+        Alignment::Last => (diff, 0),
+        _ => todo!(),
+    }
 }
 
-fn count_chars(line: &[u16], color_mode: &ColorMode) -> usize {
-    match color_mode {
-        // `count_chars` for ColorMode::Html is not implemented yet!!
+// It splits a line into graphemes. If `color_mode` is a terminal mode,
+// the ANSI escape sequences are attached to the next grapheme, so they don't take any cell.
+fn parse_line(line: &str, color_mode: &ColorMode) -> Vec<Grapheme> {
+    let mut result = vec![];
+    let mut pending_escapes = String::new();
+    let mut rest = line;
+
+    while !rest.is_empty() {
+        let (text, escape, next) = match find_escape_sequence(rest, color_mode) {
+            Some((start, end)) => (&rest[..start], &rest[start..end], &rest[end..]),
+            None => (rest, "", ""),
+        };
+
+        for g in text.graphemes(true) {
+            if g == "\r" {
+                continue;
+            }
+
+            let width = grapheme_width(g);
+            let mut chars = g.chars();
+
+            let grapheme = match (chars.next(), chars.next()) {
+                (Some(c), None) if pending_escapes.is_empty() => Grapheme::Char(c),
+                _ => Grapheme::Cluster(format!("{pending_escapes}{g}").into_boxed_str()),
+            };
+
+            pending_escapes.clear();
+            result.push(grapheme);
+
+            if width == 2 {
+                result.push(Grapheme::WideTail);
+            }
+        }
+
+        pending_escapes.push_str(escape);
+        rest = next;
+    }
+
+    // end markers at the end of the line
+    if !pending_escapes.is_empty() {
+        if let Some(last) = result.iter_mut().rev().find(|g| **g != Grapheme::WideTail) {
+            let mut buffer = String::new();
+            last.push_to(&mut buffer);
+            buffer.push_str(&pending_escapes);
+            *last = Grapheme::Cluster(buffer.into_boxed_str());
+        }
+    }
+
+    result
+}
+
+// (start, end) of the first escape sequence in `s`
+fn find_escape_sequence(s: &str, color_mode: &ColorMode) -> Option<(usize, usize)> {
+    let head = match color_mode {
+        // `ColorMode::Html` is not supported yet!!
         // that means you cannot merge 2 graphs whose color mode is html
         ColorMode::None
-        | ColorMode::Html { .. } => line.len(),
-
-        ColorMode::TerminalFg
-        | ColorMode::TerminalBg => {
-            let mut curr_state = AnsiEscapeParseState::None;
-            let mut char_count = 0;
-            let head = if let ColorMode::TerminalFg = color_mode { '3' } else { '4' } as u16;
-
-            for c in line.iter() {
-                match curr_state {
-                    AnsiEscapeParseState::None => {
-                        if *c == 27 {
-                            curr_state = AnsiEscapeParseState::Esc0;
-                        }
-
-                        else {
-                            char_count += 1;
-                        }
-                    },
-                    AnsiEscapeParseState::Esc0 => {
-                        if *c == '[' as u16 {
-                            curr_state = AnsiEscapeParseState::Esc1;
-                        }
-
-                        else if *c == 27 {
-                            char_count += 1;
-                        }
-
-                        else {
-                            curr_state = AnsiEscapeParseState::None;
-                            char_count += 2;
-                        }
-                    },
-                    AnsiEscapeParseState::Esc1 => {
-                        if *c == head {
-                            curr_state = AnsiEscapeParseState::Esc2;
-                        }
-
-                        else if *c == 27 {
-                            curr_state = AnsiEscapeParseState::Esc0;
-                            char_count += 2;
-                        }
-
-                        else {
-                            curr_state = AnsiEscapeParseState::None;
-                            char_count += 3;
-                        }
-                    },
-                    AnsiEscapeParseState::Esc2 => {
-                        if *c == 'm' as u16 {
-                            curr_state = AnsiEscapeParseState::None;
-                        }
-                    },
-                }
+        | ColorMode::Html { .. } => { return None; },
+        ColorMode::TerminalFg => "\x1b[3",
+        ColorMode::TerminalBg => "\x1b[4",
+    };
+    let start = s.find(head)?;
+    let end = s[start..].find('m')?;
+
+    Some((start, start + end + 1))
+}
+
+pub(crate) fn strip_escape_sequences(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut in_escape = false;
+
+    for c in s.chars() {
+        if c == '\x1b' {
+            in_escape = true;
+        }
+
+        else if in_escape {
+            if c == 'm' {
+                in_escape = false;
             }
+        }
 
-            char_count
-        },
+        else {
+            result.push(c);
+        }
     }
+
+    result
 }
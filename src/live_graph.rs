@@ -0,0 +1,436 @@
+use crate::charset::Charset;
+use crate::color::{Color, ColorMode};
+use crate::downsampling::Downsampling;
+use crate::format::NumberFormatter;
+use crate::graph::Graph;
+use crate::gridlines::Gridlines;
+use crate::legend::Legend;
+use crate::skip_policy::SkipPolicy;
+use crate::skip_value::SkipValue;
+use hmath::Ratio;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+/// A 1-dimensional graph of the latest `capacity` values, for live metrics. When it's full, `push` drops the oldest value.
+///
+/// The values are converted to `Ratio` only once, when they're pushed, and the minimum and the maximum are
+/// tracked incrementally, so that drawing it doesn't have to sort the data on every tick.
+/// If the data is longer than the plot width, it's downsampled and the engine has to look at the downsampled data again,
+/// so it's the fastest when `capacity` is not larger than the plot width.
+///
+/// The styles (size, title, labels, colors...) come from a `Graph`, whose data is ignored.
+///
+/// ```nohighlight
+/// let mut live = LiveGraph::new(60);
+/// live.style_mut().set_title("CPU").set_plot_height(10);
+///
+/// loop {
+///     live.push(read_cpu_usage());
+///     println!("{live}");
+/// }
+/// ```
+#[derive(Clone)]
+pub struct LiveGraph {
+    // The styles. The current values are copied to its data only when it's drawn, and the buffer of the data is reused.
+    // It's in a `RefCell` because `Display` draws it with `&self`.
+    graph: RefCell<Graph>,
+    capacity: usize,
+
+    // (label, value), the label is `None` if it's pushed by `push`, and it's the index of the push
+    data: VecDeque<(Option<String>, Ratio)>,
+
+    // number of values pushed so far, including the dropped ones
+    pushed: usize,
+
+    // Monotonic queues of (index of the push, value). The values in `min_queue` are increasing, and the ones in `max_queue` are decreasing,
+    // so the fronts are the minimum and the maximum of the current data.
+    min_queue: VecDeque<(usize, Ratio)>,
+    max_queue: VecDeque<(usize, Ratio)>,
+}
+
+impl LiveGraph {
+    /// The plot is `capacity` characters wide, so each value takes a column. It doesn't skip the y axis by default,
+    /// because the skipped ranges would jump around on every tick.
+    pub fn new(capacity: usize) -> Self {
+        let mut style = Graph::default();
+        style.set_plot_width(capacity).set_skip_range(SkipValue::None);
+
+        LiveGraph::with_style(capacity, &style)
+    }
+
+    /// It uses all the settings of `style` except its data.
+    pub fn with_style(capacity: usize, style: &Graph) -> Self {
+        assert!(capacity > 0, "The capacity of a LiveGraph must be greater than 0");
+
+        let mut graph = style.clone();
+        graph.set_1d_data_with_range(Vec::with_capacity(capacity), None);
+
+        LiveGraph {
+            graph: RefCell::new(graph),
+            capacity,
+
+            // `+ 1` because `push` drops the oldest value after it pushes a new one
+            data: VecDeque::with_capacity(capacity + 1),
+            pushed: 0,
+            min_queue: VecDeque::new(),
+            max_queue: VecDeque::new(),
+        }
+    }
+
+    /// You can change the styles of the graph with it. See `LiveGraphStyle`.
+    pub fn style_mut(&mut self) -> LiveGraphStyle<'_> {
+        LiveGraphStyle {
+            graph: self.graph.get_mut(),
+        }
+    }
+
+    /// The label of the value is the number of values pushed before it, which is the index of the value if nothing's dropped.
+    ///
+    /// `T` can be any number type, including f32 and f64. NaN is converted to 0, -Inf is converted to f32::MIN and Inf to f32::MAX (or f64).
+    pub fn push<T: TryInto<Ratio>>(&mut self, value: T) -> &mut Self {
+        self.push_value(None, value)
+    }
+
+    /// `T` can be any number type, including f32 and f64. NaN is converted to 0, -Inf is converted to f32::MIN and Inf to f32::MAX (or f64).
+    pub fn push_labeled<T: TryInto<Ratio>>(&mut self, label: String, value: T) -> &mut Self {
+        self.push_value(Some(label), value)
+    }
+
+    fn push_value<T: TryInto<Ratio>>(&mut self, label: Option<String>, value: T) -> &mut Self {
+        let value = value.try_into().unwrap_or(Ratio::zero());
+        let index = self.pushed;
+
+        while let Some((_, n)) = self.min_queue.back() {
+            if n.geq_rat(&value) {
+                self.min_queue.pop_back();
+            }
+
+            else {
+                break;
+            }
+        }
+
+        while let Some((_, n)) = self.max_queue.back() {
+            if n.leq_rat(&value) {
+                self.max_queue.pop_back();
+            }
+
+            else {
+                break;
+            }
+        }
+
+        self.min_queue.push_back((index, value.clone()));
+        self.max_queue.push_back((index, value.clone()));
+        self.data.push_back((label, value));
+        self.pushed += 1;
+
+        if self.data.len() > self.capacity {
+            self.data.pop_front();
+
+            // the index of the oldest value that's still in `self.data`
+            let first = self.pushed - self.capacity;
+
+            if self.min_queue[0].0 < first {
+                self.min_queue.pop_front();
+            }
+
+            if self.max_queue[0].0 < first {
+                self.max_queue.pop_front();
+            }
+        }
+
+        self
+    }
+
+    /// It removes all the values. The labels of `push` start from 0 again.
+    pub fn clear(&mut self) -> &mut Self {
+        self.data.clear();
+        self.min_queue.clear();
+        self.max_queue.clear();
+        self.pushed = 0;
+
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// the minimum of the current values, `None` if it's empty
+    pub fn get_min(&self) -> Option<&Ratio> {
+        self.min_queue.front().map(|(_, n)| n)
+    }
+
+    /// the maximum of the current values, `None` if it's empty
+    pub fn get_max(&self) -> Option<&Ratio> {
+        self.max_queue.front().map(|(_, n)| n)
+    }
+
+    /// A `Graph` with the styles and the current values. It's useful if you want to put it in a `Dashboard`, or call `render_with_metadata`.
+    pub fn to_graph(&self) -> Graph {
+        let mut graph = self.graph.borrow_mut();
+        self.fill_graph(&mut graph);
+
+        graph.clone()
+    }
+
+    /// It panics if it's empty, or the styles are not well-configured.
+    pub fn draw(&self) -> String {
+        let mut graph = self.graph.borrow_mut();
+        self.fill_graph(&mut graph);
+
+        graph.draw()
+    }
+
+    // It copies the current values to the data of `graph`, reusing the buffer of its previous data.
+    fn fill_graph(&self, graph: &mut Graph) {
+        let mut data = graph.take_1d_data();
+        data.clear();
+
+        // the index of the push of `self.data[0]`
+        let first = self.pushed - self.data.len();
+
+        data.extend(self.data.iter().enumerate().map(
+            |(index, (label, value))| (
+                label.clone().unwrap_or_else(|| (first + index).to_string()),
+                value.clone(),
+            )
+        ));
+
+        let data_range = match (self.get_min(), self.get_max()) {
+            (Some(min), Some(max)) => Some((min.clone(), max.clone())),
+            _ => None,
+        };
+
+        graph.set_1d_data_with_range(data, data_range);
+    }
+}
+
+/// The styles of a `LiveGraph`, from `LiveGraph::style_mut`. The setters are the same as the ones of `Graph`.
+///
+/// It only has the setters that don't depend on the data. The labeled intervals, the annotations and the horizontal breaks
+/// are indices of the data, which move on every push, so use `LiveGraph::with_style` if you really need them.
+pub struct LiveGraphStyle<'a> {
+    graph: &'a mut Graph,
+}
+
+impl LiveGraphStyle<'_> {
+    pub fn set_title<T: ToString>(&mut self, title: T) -> &mut Self {
+        self.graph.set_title(title);
+
+        self
+    }
+
+    pub fn set_title_color(&mut self, title_color: Option<Color>) -> &mut Self {
+        self.graph.set_title_color(title_color);
+
+        self
+    }
+
+    pub fn set_big_title(&mut self, big_title: bool) -> &mut Self {
+        self.graph.set_big_title(big_title);
+
+        self
+    }
+
+    pub fn set_plot_width(&mut self, plot_width: usize) -> &mut Self {
+        self.graph.set_plot_width(plot_width);
+
+        self
+    }
+
+    pub fn set_plot_height(&mut self, plot_height: usize) -> &mut Self {
+        self.graph.set_plot_height(plot_height);
+
+        self
+    }
+
+    pub fn set_block_width(&mut self, block_width: usize) -> &mut Self {
+        self.graph.set_block_width(block_width);
+
+        self
+    }
+
+    pub fn set_paddings(&mut self, paddings: [usize; 4]) -> &mut Self {
+        self.graph.set_paddings(paddings);
+
+        self
+    }
+
+    pub fn set_x_label_margin(&mut self, x_label_margin: usize) -> &mut Self {
+        self.graph.set_x_label_margin(x_label_margin);
+
+        self
+    }
+
+    pub fn set_y_label_margin(&mut self, y_label_margin: usize) -> &mut Self {
+        self.graph.set_y_label_margin(y_label_margin);
+
+        self
+    }
+
+    pub fn set_x_axis_label<T: ToString>(&mut self, x_axis_label: T) -> &mut Self {
+        self.graph.set_x_axis_label(x_axis_label);
+
+        self
+    }
+
+    pub fn set_y_axis_label<T: ToString>(&mut self, y_axis_label: T) -> &mut Self {
+        self.graph.set_y_axis_label(y_axis_label);
+
+        self
+    }
+
+    pub fn set_y_min<T: TryInto<Ratio>>(&mut self, y_min: T) -> &mut Self {
+        self.graph.set_y_min(y_min);
+
+        self
+    }
+
+    pub fn set_y_max<T: TryInto<Ratio>>(&mut self, y_max: T) -> &mut Self {
+        self.graph.set_y_max(y_max);
+
+        self
+    }
+
+    pub fn set_y_range<T: TryInto<Ratio>, U: TryInto<Ratio>>(&mut self, y_min: T, y_max: U) -> &mut Self {
+        self.graph.set_y_range(y_min, y_max);
+
+        self
+    }
+
+    pub fn set_pretty_y<T: TryInto<Ratio>>(&mut self, y: T) -> &mut Self {
+        self.graph.set_pretty_y(y);
+
+        self
+    }
+
+    pub fn set_show_y_labels(&mut self, show_y_labels: bool) -> &mut Self {
+        self.graph.set_show_y_labels(show_y_labels);
+
+        self
+    }
+
+    pub fn set_y_label_width(&mut self, y_label_width: usize) -> &mut Self {
+        self.graph.set_y_label_width(y_label_width);
+
+        self
+    }
+
+    pub fn set_y_label_formatter(&mut self, formatter: Box<dyn NumberFormatter>) -> &mut Self {
+        self.graph.set_y_label_formatter(formatter);
+
+        self
+    }
+
+    pub fn set_downsampling(&mut self, downsampling: Downsampling) -> &mut Self {
+        self.graph.set_downsampling(downsampling);
+
+        self
+    }
+
+    pub fn set_skip_range(&mut self, skip_value: SkipValue) -> &mut Self {
+        self.graph.set_skip_range(skip_value);
+
+        self
+    }
+
+    pub fn set_skip_policy(&mut self, skip_policy: SkipPolicy) -> &mut Self {
+        self.graph.set_skip_policy(skip_policy);
+
+        self
+    }
+
+    pub fn set_gridlines(&mut self, gridlines: Gridlines) -> &mut Self {
+        self.graph.set_gridlines(gridlines);
+
+        self
+    }
+
+    pub fn set_gridline_color(&mut self, color: Option<Color>) -> &mut Self {
+        self.graph.set_gridline_color(color);
+
+        self
+    }
+
+    pub fn set_legend(&mut self, legend: Option<Legend>) -> &mut Self {
+        self.graph.set_legend(legend);
+
+        self
+    }
+
+    pub fn set_primary_color(&mut self, color: Option<Color>) -> &mut Self {
+        self.graph.set_primary_color(color);
+
+        self
+    }
+
+    pub fn set_color_mode(&mut self, color_mode: ColorMode) -> &mut Self {
+        self.graph.set_color_mode(color_mode);
+
+        self
+    }
+
+    pub fn set_charset(&mut self, charset: Charset) -> &mut Self {
+        self.graph.set_charset(charset);
+
+        self
+    }
+}
+
+use std::fmt;
+
+// An empty `LiveGraph` is an empty string, so that it can be printed before the first `push`.
+impl fmt::Display for LiveGraph {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        write!(fmt, "{}", self.draw())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_the_oldest_values() {
+        let mut live = LiveGraph::new(10);
+        live.style_mut().set_plot_height(6);
+
+        for n in 0..15 {
+            live.push((n * 7) % 11);
+        }
+
+        assert_eq!(live.len(), 10);
+        assert_eq!(live.get_min(), Some(&Ratio::from_i32(0)));
+        assert_eq!(live.get_max(), Some(&Ratio::from_i32(10)));
+
+        let mut graph = Graph::new(10, 6);
+        graph.set_skip_range(SkipValue::None)
+            .set_1d_labeled_data(&(5..15).map(|n| (n.to_string(), (n * 7) % 11)).collect::<Vec<_>>());
+
+        assert_eq!(live.to_string(), graph.draw());
+    }
+
+    #[test]
+    fn empty_live_graph() {
+        let mut live = LiveGraph::new(10);
+        assert_eq!(live.to_string(), "");
+
+        live.push(1).clear();
+        assert_eq!(live.to_string(), "");
+        assert_eq!(live.get_min(), None);
+    }
+}
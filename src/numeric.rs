@@ -0,0 +1,167 @@
+use hmath::Ratio;
+use std::cmp::Ordering;
+
+/// The numbers that the engine can do math with. `Ratio` is exact, and `f64` is much faster but not exact.
+///
+/// The per-point work (downsampling, resampling time series...) is generic over it, and the results are converted
+/// to `Ratio`, so that the y axis and the labels are always exact.
+pub(crate) trait Number: Clone + PartialOrd {
+    fn zero() -> Self;
+    fn from_usize(n: usize) -> Self;
+
+    fn add(&self, other: &Self) -> Self;
+    fn add_mut(&mut self, other: &Self);
+    fn sub(&self, other: &Self) -> Self;
+    fn mul(&self, other: &Self) -> Self;
+    fn div_usize(&self, n: usize) -> Self;
+    fn abs(&self) -> Self;
+
+    fn to_ratio(&self) -> Ratio;
+
+    // NaN is never stored, so it's a total order
+    fn cmp_num(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl Number for Ratio {
+    fn zero() -> Self {
+        Ratio::zero()
+    }
+
+    fn from_usize(n: usize) -> Self {
+        Ratio::from_i64(n as i64)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self.add_rat(other)
+    }
+
+    fn add_mut(&mut self, other: &Self) {
+        self.add_rat_mut(other);
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self.sub_rat(other)
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self.mul_rat(other)
+    }
+
+    fn div_usize(&self, n: usize) -> Self {
+        self.div_i32(n as i32)
+    }
+
+    fn abs(&self) -> Self {
+        Ratio::abs(self)
+    }
+
+    fn to_ratio(&self) -> Ratio {
+        self.clone()
+    }
+}
+
+impl Number for f64 {
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn from_usize(n: usize) -> Self {
+        n as f64
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn add_mut(&mut self, other: &Self) {
+        *self += other;
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self - other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn div_usize(&self, n: usize) -> Self {
+        self / n as f64
+    }
+
+    fn abs(&self) -> Self {
+        f64::abs(*self)
+    }
+
+    fn to_ratio(&self) -> Ratio {
+        Ratio::try_from(*self).unwrap_or(Ratio::zero())
+    }
+}
+
+// The values of 1-dimensional data and time series. `K` is the label or the timestamp of each value.
+// The data set by `set_1d_data_f64` and the like is stored as `F64`, and the others are `Exact`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Series<K> {
+    Exact(Vec<(K, Ratio)>),
+    F64(Vec<(K, f64)>),
+}
+
+impl<K> Series<K> {
+    pub fn len(&self) -> usize {
+        match self {
+            Series::Exact(data) => data.len(),
+            Series::F64(data) => data.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get_key(&self, index: usize) -> &K {
+        match self {
+            Series::Exact(data) => &data[index].0,
+            Series::F64(data) => &data[index].0,
+        }
+    }
+
+    // (min, max) of the values, in a single pass. It's `None` if it's empty.
+    pub fn get_range(&self) -> Option<(Ratio, Ratio)> {
+        match self {
+            Series::Exact(data) => get_range(data),
+            Series::F64(data) => get_range(data),
+        }
+    }
+}
+
+fn get_range<K, N: Number>(data: &[(K, N)]) -> Option<(Ratio, Ratio)> {
+    let (_, first) = data.first()?;
+    let mut min = first;
+    let mut max = first;
+
+    for (_, n) in data.iter() {
+        if n < min {
+            min = n;
+        }
+
+        if n > max {
+            max = n;
+        }
+    }
+
+    Some((min.to_ratio(), max.to_ratio()))
+}
+
+// f64 can't be NaN or infinite in `Series`, so that the math is always valid.
+// It follows the conversion of `Ratio`: NaN is 0, and the infinities are the largest finite numbers.
+pub(crate) fn sanitize_f64(n: f64) -> f64 {
+    if n.is_nan() {
+        0.0
+    }
+
+    else {
+        n.clamp(f64::MIN, f64::MAX)
+    }
+}
@@ -0,0 +1,27 @@
+use std::str::FromStr;
+
+/// The direction of the bars (or boxes) of a graph.
+///
+/// It's default to `Orientation::Vertical`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Orientation {
+    /// The values are on the y axis.
+    #[default]
+    Vertical,
+
+    /// The values are on the x axis, and the categories are on the y axis.
+    Horizontal,
+}
+
+impl FromStr for Orientation {
+    type Err = String;
+
+    /// returns Err(s) if it fails
+    fn from_str(s: &str) -> Result<Orientation, String> {
+        match s.replace(" ", "").replace("_", "").replace("-", "").to_ascii_lowercase() {
+            s if s == "vertical" => Ok(Orientation::Vertical),
+            s if s == "horizontal" => Ok(Orientation::Horizontal),
+            _ => Err(s.to_string()),
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use crate::alignment::Alignment;
+use crate::charset::{Charset, Glyphs};
+use crate::color::{Color, ColorMode};
+use crate::format::{DefaultFormatter, NumberFormatter};
+use crate::lines::Lines;
+use hmath::Ratio;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// It's default to `ProportionStyle::Bar`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ProportionStyle {
+    /// a single 100% stacked bar
+    #[default]
+    Bar,
+
+    /// a coarse pie chart
+    Pie,
+}
+
+impl FromStr for ProportionStyle {
+    type Err = String;
+
+    /// returns Err(s) if it fails
+    fn from_str(s: &str) -> Result<ProportionStyle, String> {
+        match s.replace(" ", "").replace("_", "").replace("-", "").to_ascii_lowercase() {
+            s if s == "bar" => Ok(ProportionStyle::Bar),
+            s if s == "pie" => Ok(ProportionStyle::Pie),
+            _ => Err(s.to_string()),
+        }
+    }
+}
+
+/// It shows the share of each segment, as a 100% stacked bar or a pie.
+///
+/// ```nohighlight
+/// ████████████████▓▓▓▓▓▓▓▓▓▓▒▒▒▒
+/// █ web 53.3%
+/// ▓ api 33.3%
+/// ▒ etc 13.3%
+/// ```
+///
+/// Like `Graph`, it can be merged with other graphs using `merge_horiz` and `merge_vert`.
+#[derive(Clone)]
+pub struct ProportionChart {
+    // (label, value, color)
+    segments: Vec<(String, Ratio, Option<Color>)>,
+
+    style: ProportionStyle,
+
+    // width and height of the bar
+    bar_width: usize,
+    bar_height: usize,
+
+    // radius of the pie, in rows
+    radius: usize,
+
+    show_legend: bool,
+
+    // it formats the percentages in the legend
+    formatter: Arc<dyn NumberFormatter>,
+
+    color_mode: ColorMode,
+    charset: Charset,
+}
+
+impl ProportionChart {
+    /// `T` can be any number type, including f32 and f64. NaN is converted to 0, -Inf is converted to f32::MIN and Inf to f32::MAX (or f64).\
+    /// Negative values are treated as 0. If `color` is `None`, or the color mode is `ColorMode::None`, the segment is filled with a shade instead.
+    /// There're only 4 shades, so without colors, the 5th segment looks the same as the first one, and so on.
+    /// The legend is in the same order as the segments, so you can still tell them apart.
+    pub fn add_segment<T: TryInto<Ratio>, U: ToString>(&mut self, label: U, value: T, color: Option<Color>) -> &mut Self {
+        let value = value.try_into().unwrap_or(Ratio::zero());
+        let value = if value.is_neg() { Ratio::zero() } else { value };
+
+        self.segments.push((label.to_string(), value, color));
+
+        self
+    }
+
+    pub fn set_style(&mut self, style: ProportionStyle) -> &mut Self {
+        self.style = style;
+
+        self
+    }
+
+    /// It's only for `ProportionStyle::Bar`.
+    pub fn set_bar_width(&mut self, bar_width: usize) -> &mut Self {
+        self.bar_width = bar_width;
+
+        self
+    }
+
+    /// It's only for `ProportionStyle::Bar`.
+    pub fn set_bar_height(&mut self, bar_height: usize) -> &mut Self {
+        self.bar_height = bar_height;
+
+        self
+    }
+
+    /// It's only for `ProportionStyle::Pie`. The pie is `radius * 2` rows high and `radius * 4` characters wide.
+    pub fn set_radius(&mut self, radius: usize) -> &mut Self {
+        self.radius = radius;
+
+        self
+    }
+
+    pub fn set_show_legend(&mut self, show_legend: bool) -> &mut Self {
+        self.show_legend = show_legend;
+
+        self
+    }
+
+    /// It formats the percentages in the legend. The input is 0 ~ 100, and `%` is appended to the output.
+    pub fn set_formatter(&mut self, formatter: Box<dyn NumberFormatter>) -> &mut Self {
+        self.formatter = formatter.into();
+
+        self
+    }
+
+    pub fn set_color_mode(&mut self, color_mode: ColorMode) -> &mut Self {
+        self.color_mode = color_mode;
+
+        self
+    }
+
+    pub fn set_charset(&mut self, charset: Charset) -> &mut Self {
+        self.charset = charset;
+
+        self
+    }
+
+    pub fn draw(&self) -> String {
+        self.draw_lines().to_string(&self.color_mode)
+    }
+
+    pub(crate) fn draw_lines(&self) -> Lines {
+        let glyphs = self.charset.glyphs();
+        let fills = self.segments.iter().enumerate().map(
+            |(index, (_, _, color))| (segment_glyph(index, color, &self.color_mode, glyphs), color.clone())
+        ).collect::<Vec<_>>();
+
+        let chart = match self.style {
+            ProportionStyle::Bar => self.draw_bar(&fills),
+            ProportionStyle::Pie => self.draw_pie(&fills),
+        };
+
+        if !self.show_legend || self.segments.is_empty() {
+            return chart;
+        }
+
+        let legend = self.draw_legend(&fills);
+
+        match self.style {
+            ProportionStyle::Bar => chart.merge_vertically(&legend, Alignment::First),
+            ProportionStyle::Pie => chart.merge_horizontally(&legend.add_padding([0, 0, 2, 0]), Alignment::Center),
+        }
+    }
+
+    // `fills` are (glyph, color) of each segment
+    fn draw_bar(&self, fills: &[(char, Option<Color>)]) -> Lines {
+        let mut result = Lines::new(self.bar_width, self.bar_height);
+        let mut x = 0;
+
+        for (index, width) in self.get_bar_widths().into_iter().enumerate() {
+            let (glyph, color) = &fills[index];
+
+            for _ in 0..width {
+                for y in 0..self.bar_height {
+                    result.set(x, y, *glyph);
+                    result.set_color(x, y, color.clone());
+                }
+
+                x += 1;
+            }
+        }
+
+        result
+    }
+
+    // Each segment gets `floor(share * bar_width)` characters, and the remaining characters go to
+    // the segments with the largest remainders, so that the widths always add up to `bar_width`.
+    fn get_bar_widths(&self) -> Vec<usize> {
+        let total = self.get_total();
+
+        if total.is_zero() {
+            return vec![0; self.segments.len()];
+        }
+
+        let mut widths = Vec::with_capacity(self.segments.len());
+        let mut remainders = Vec::with_capacity(self.segments.len());
+
+        for (index, (_, value, _)) in self.segments.iter().enumerate() {
+            let width = value.mul_i32(self.bar_width as i32).div_rat(&total);
+            let truncated = width.truncate();
+
+            widths.push(truncated.truncate_bi().to_i32().unwrap_or(0) as usize);
+            remainders.push((width.sub_rat(&truncated), index));
+        }
+
+        // the earlier segment wins a tie
+        remainders.sort_by(|(r1, i1), (r2, i2)| r2.cmp(r1).then(i1.cmp(i2)));
+
+        for (_, index) in remainders.into_iter().take(self.bar_width - widths.iter().sum::<usize>()) {
+            widths[index] += 1;
+        }
+
+        widths
+    }
+
+    // It starts at 12 o'clock and goes clockwise.
+    fn draw_pie(&self, fills: &[(char, Option<Color>)]) -> Lines {
+        let (width, height) = (self.radius * 4, self.radius * 2);
+        let mut result = Lines::new(width, height);
+        let total = self.get_total();
+
+        if total.is_zero() {
+            return result;
+        }
+
+        // the end of each segment, in turns (0 ~ 1)
+        let mut ends = Vec::with_capacity(self.segments.len());
+        let mut sum = Ratio::zero();
+
+        for (_, value, _) in self.segments.iter() {
+            sum.add_rat_mut(value);
+            ends.push(f64::try_from(sum.div_rat(&total)).unwrap_or(1.0));
+        }
+
+        let radius = self.radius as f64;
+
+        for y in 0..height {
+            for x in 0..width {
+                // a character is twice as high as it's wide
+                let dx = (x as f64 + 0.5 - radius * 2.0) / 2.0;
+                let dy = y as f64 + 0.5 - radius;
+
+                if dx * dx + dy * dy > radius * radius {
+                    continue;
+                }
+
+                let turn = dx.atan2(-dy).rem_euclid(std::f64::consts::TAU) / std::f64::consts::TAU;
+                let index = ends.iter().position(|end| turn < *end).unwrap_or(ends.len() - 1);
+                let (glyph, color) = &fills[index];
+
+                result.set(x, y, *glyph);
+                result.set_color(x, y, color.clone());
+            }
+        }
+
+        result
+    }
+
+    fn draw_legend(&self, fills: &[(char, Option<Color>)]) -> Lines {
+        let total = self.get_total();
+        let mut result = Lines::empty();
+
+        for (index, (label, value, _)) in self.segments.iter().enumerate() {
+            let percentage = if total.is_zero() {
+                Ratio::zero()
+            } else {
+                value.mul_i32(100).div_rat(&total)
+            };
+
+            let (glyph, color) = &fills[index];
+            let mut sample = Lines::new(1, 1);
+            sample.set(0, 0, *glyph);
+            sample.set_color(0, 0, color.clone());
+
+            let label = Lines::from_string(
+                &format!(" {} {}%", label.replace('\n', " "), self.formatter.f(&percentage)),
+                Alignment::First,
+                &ColorMode::None,
+            );
+            result = result.merge_vertically(&sample.merge_horizontally(&label, Alignment::First), Alignment::First);
+        }
+
+        result
+    }
+
+    fn get_total(&self) -> Ratio {
+        let mut total = Ratio::zero();
+
+        for (_, value, _) in self.segments.iter() {
+            total.add_rat_mut(value);
+        }
+
+        total
+    }
+}
+
+// Colored segments are filled with full blocks, and the others with shades, from the darkest one.
+// If the colors are not rendered, the colored segments are shaded too, otherwise they'd look like a single bar.
+fn segment_glyph(index: usize, color: &Option<Color>, color_mode: &ColorMode, glyphs: &Glyphs) -> char {
+    match (color, color_mode) {
+        (Some(_), ColorMode::None) | (None, _) => glyphs.shades[3 - index % 4],
+        (Some(_), _) => glyphs.bars[0],
+    }
+}
+
+impl Default for ProportionChart {
+    fn default() -> Self {
+        ProportionChart {
+            segments: vec![],
+            style: ProportionStyle::Bar,
+            bar_width: 40,
+            bar_height: 1,
+            radius: 5,
+            show_legend: true,
+            formatter: Arc::new(DefaultFormatter),
+            color_mode: ColorMode::None,
+            charset: Charset::Unicode,
+        }
+    }
+}
+
+use std::fmt;
+
+impl fmt::Display for ProportionChart {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.draw())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colored_segments_without_colors() {
+        let mut chart = ProportionChart::default();
+        chart.add_segment("a", 1, Some(Color::Red))
+            .add_segment("b", 1, Some(Color::Blue))
+            .set_bar_width(4)
+            .set_show_legend(false);
+
+        assert_eq!(chart.draw(), "██▓▓");
+
+        chart.set_color_mode(ColorMode::TerminalFg);
+        assert_eq!(chart.draw_lines().to_string(&ColorMode::None), "████");
+    }
+}
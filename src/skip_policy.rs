@@ -0,0 +1,158 @@
+use hmath::Ratio;
+use std::str::FromStr;
+
+/// How the height of the plot is split between the subplots, when the y axis is skipped.
+/// It's default to `HeightAllocation::Density`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum HeightAllocation {
+    /// All the subplots have the same height.
+    Equal,
+
+    /// proportional to the number of distinct values in each subplot
+    #[default]
+    Density,
+
+    /// proportional to the range of the values in each subplot, so that a row means similar amount in every subplot
+    Range,
+}
+
+impl FromStr for HeightAllocation {
+    type Err = String;
+
+    /// returns Err(s) if it fails
+    fn from_str(s: &str) -> Result<HeightAllocation, String> {
+        match s.replace(" ", "").replace("_", "").replace("-", "").to_ascii_lowercase() {
+            s if s == "equal" => Ok(HeightAllocation::Equal),
+            s if s == "density" => Ok(HeightAllocation::Density),
+            s if s == "range" => Ok(HeightAllocation::Range),
+            _ => Err(s.to_string()),
+        }
+    }
+}
+
+/// It decides when and how the y axis is skipped.
+///
+/// `min_plot_height`, `min_subplot_height` and `height_allocation` are applied to both `SkipValue::Automatic` and `SkipValue::Manual`.
+/// The other options are only for `SkipValue::Automatic`.
+///
+/// ```nohighlight
+/// let mut policy = SkipPolicy::default();
+/// policy.set_min_plot_height(10).set_height_allocation(HeightAllocation::Equal);
+/// graph.set_skip_policy(policy);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct SkipPolicy {
+    pub(crate) min_plot_height: usize,
+    pub(crate) min_gap_ratio: Ratio,
+    pub(crate) max_ranges: Option<usize>,
+    pub(crate) padding: Ratio,
+    pub(crate) min_subplot_height: usize,
+    pub(crate) height_allocation: HeightAllocation,
+
+    // (from, to), `None` is an open end
+    pub(crate) never_skip: Option<(Option<Ratio>, Option<Ratio>)>,
+}
+
+impl SkipPolicy {
+    /// It doesn't skip anything if the plot is lower than this. It's default to 19.
+    pub fn set_min_plot_height(&mut self, min_plot_height: usize) -> &mut Self {
+        self.min_plot_height = min_plot_height;
+
+        self
+    }
+
+    /// It skips a gap only if the gap is larger than `1 / min_gap_ratio` of the range of the values.
+    /// For the second and the later gaps, the range doesn't include the largest gap. It's default to 3.
+    /// It panics if `min_gap_ratio` is not a number or is not positive.
+    pub fn set_min_gap_ratio<T: TryInto<Ratio>>(&mut self, min_gap_ratio: T) -> &mut Self {
+        let Ok(min_gap_ratio) = min_gap_ratio.try_into() else {
+            panic!("`min_gap_ratio` must be a number");
+        };
+        assert!(min_gap_ratio.gt_rat(&Ratio::zero()), "`min_gap_ratio` must be positive, got {min_gap_ratio}");
+        self.min_gap_ratio = min_gap_ratio;
+
+        self
+    }
+
+    /// The maximum number of skipped ranges. By default, it skips as many ranges as the height allows.
+    pub fn set_max_ranges(&mut self, max_ranges: Option<usize>) -> &mut Self {
+        self.max_ranges = max_ranges;
+
+        self
+    }
+
+    /// The space above and below the values of each subplot, relative to the range of the values. It's default to 1/16.
+    /// It panics if `padding` is not a number or is not positive.
+    pub fn set_padding<T: TryInto<Ratio>>(&mut self, padding: T) -> &mut Self {
+        let Ok(padding) = padding.try_into() else {
+            panic!("`padding` must be a number");
+        };
+        assert!(padding.gt_rat(&Ratio::zero()), "`padding` must be positive, got {padding}");
+        self.padding = padding;
+
+        self
+    }
+
+    /// Each subplot is at least this high, if possible. It's default to 3.
+    pub fn set_min_subplot_height(&mut self, min_subplot_height: usize) -> &mut Self {
+        self.min_subplot_height = min_subplot_height.max(1);
+
+        self
+    }
+
+    pub fn set_height_allocation(&mut self, height_allocation: HeightAllocation) -> &mut Self {
+        self.height_allocation = height_allocation;
+
+        self
+    }
+
+    /// It never skips a range that's inside this range. You can set open ends with `None` values.
+    /// It used to be `Graph::set_skip_skip_range`.
+    pub fn set_never_skip<T: TryInto<Ratio>, U: TryInto<Ratio>>(&mut self, from: Option<T>, to: Option<U>) -> &mut Self {
+        self.never_skip = Some((
+            from.map(|n| n.try_into().unwrap_or(Ratio::zero())),
+            to.map(|n| n.try_into().unwrap_or(Ratio::zero())),
+        ));
+
+        self
+    }
+
+    // `(from, to)` is not inside `self.never_skip`
+    pub(crate) fn allows(&self, from: &Ratio, to: &Ratio) -> bool {
+        match &self.never_skip {
+            Some((never_skip_from, never_skip_to)) => !match (never_skip_from, never_skip_to) {
+                (Some(never_skip_from), Some(never_skip_to)) => never_skip_from.lt_rat(from) && never_skip_to.gt_rat(to),
+                (None, Some(never_skip_to)) => never_skip_to.gt_rat(to),
+                (Some(never_skip_from), None) => never_skip_from.lt_rat(from),
+
+                // (-inf, inf), which includes all the numbers
+                (None, None) => true,
+            },
+            None => true,
+        }
+    }
+
+    // the number of ranges that a plot of this height can skip
+    pub(crate) fn get_max_ranges(&self, plot_height: usize) -> usize {
+        let by_height = plot_height.saturating_sub(self.min_subplot_height) / (self.min_subplot_height + 1);
+
+        match self.max_ranges {
+            Some(n) => n.min(by_height),
+            None => by_height,
+        }
+    }
+}
+
+impl Default for SkipPolicy {
+    fn default() -> Self {
+        SkipPolicy {
+            min_plot_height: 19,
+            min_gap_ratio: Ratio::from_i32(3),
+            max_ranges: None,
+            padding: Ratio::from_i32(1).div_i32(16),
+            min_subplot_height: 3,
+            height_allocation: HeightAllocation::Density,
+            never_skip: None,
+        }
+    }
+}
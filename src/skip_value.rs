@@ -4,7 +4,9 @@ use hmath::Ratio;
 pub enum SkipValue {
     None,
     Automatic,
-    Manual { from: Ratio, to: Ratio },
+
+    /// (from, to) of each range. They're sorted and don't overlap.
+    Manual(Vec<(Ratio, Ratio)>),
 }
 
 impl SkipValue {
@@ -14,7 +16,7 @@ impl SkipValue {
         SkipValue::None
     }
 
-    /// The engine decide whether to skip a range or not.
+    /// The engine decide whether to skip ranges or not, and which ones.
     pub fn automatic() -> Self {
         SkipValue::Automatic
     }
@@ -25,15 +27,37 @@ impl SkipValue {
         let to = to.try_into().unwrap_or(Ratio::zero());
         assert!(from.leq_rat(&to));
 
-        SkipValue::Manual {
-            from, to
+        SkipValue::Manual(vec![(from, to)])
+    }
+
+    /// Forces the engine to skip these ranges. Each range is drawn as a separate horizontal line.
+    /// It panics if `from > to` for any range, or if the ranges overlap. The order of the ranges doesn't matter.
+    pub fn manual_ranges<T: TryInto<Ratio> + Clone, U: TryInto<Ratio> + Clone>(ranges: &[(T, U)]) -> Self {
+        let mut ranges = ranges.iter().map(
+            |(from, to)| (
+                from.clone().try_into().unwrap_or(Ratio::zero()),
+                to.clone().try_into().unwrap_or(Ratio::zero()),
+            )
+        ).collect::<Vec<(Ratio, Ratio)>>();
+
+        ranges.sort_by(|(from1, _), (from2, _)| from1.cmp(from2));
+
+        for (index, (from, to)) in ranges.iter().enumerate() {
+            assert!(from.leq_rat(to));
+
+            if index > 0 {
+                assert!(ranges[index - 1].1.lt_rat(from));
+            }
         }
+
+        SkipValue::Manual(ranges)
     }
 
     pub(crate) fn is_automatic(&self) -> bool {
-        match self {
-            SkipValue::Automatic => true,
-            _ => false,
-        }
+        matches!(self, SkipValue::Automatic)
+    }
+
+    pub(crate) fn is_manual(&self) -> bool {
+        matches!(self, SkipValue::Manual(_))
     }
 }
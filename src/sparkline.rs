@@ -0,0 +1,234 @@
+use crate::alignment::Alignment;
+use crate::charset::Charset;
+use crate::color::{Color, ColorMode};
+use crate::downsampling::Downsampling;
+use crate::format::{DefaultFormatter, NumberFormatter};
+use crate::lines::Lines;
+use hmath::Ratio;
+use std::sync::Arc;
+
+/// A one-line graph, like `▁▂▃▅▇█▆▃`. It doesn't have any axis, label or border, so it fits in a status line.
+///
+/// Like `Graph`, it can be merged with other graphs using `merge_horiz` and `merge_vert`.
+#[derive(Clone)]
+pub struct Sparkline {
+    data: Vec<Ratio>,
+
+    // if it's not set, each value takes a character
+    width: Option<usize>,
+
+    y_min: Option<Ratio>,
+    y_max: Option<Ratio>,
+
+    show_min: bool,
+    show_max: bool,
+    show_last: bool,
+
+    formatter: Arc<dyn NumberFormatter>,
+    downsampling: Downsampling,
+
+    color_mode: ColorMode,
+    primary_color: Option<Color>,
+    annotation_color: Option<Color>,
+
+    charset: Charset,
+}
+
+impl Sparkline {
+    /// `T` can be any number type, including f32 and f64. NaN is converted to 0, -Inf is converted to f32::MIN and Inf to f32::MAX (or f64).
+    pub fn set_data<T: TryInto<Ratio> + Clone>(&mut self, data: &[T]) -> &mut Self {
+        self.data = data.iter().map(|n| n.clone().try_into().unwrap_or(Ratio::zero())).collect();
+
+        self
+    }
+
+    /// If the data is longer than `width`, it's reduced by `self.downsampling`. If it's shorter, each value takes multiple characters.
+    pub fn set_width(&mut self, width: usize) -> &mut Self {
+        self.width = Some(width);
+
+        self
+    }
+
+    /// If it's not set, the minimum of the data is used.
+    pub fn set_y_min<T: TryInto<Ratio>>(&mut self, y_min: T) -> &mut Self {
+        self.y_min = Some(y_min.try_into().unwrap_or(Ratio::zero()));
+
+        self
+    }
+
+    /// If it's not set, the maximum of the data is used.
+    pub fn set_y_max<T: TryInto<Ratio>>(&mut self, y_max: T) -> &mut Self {
+        self.y_max = Some(y_max.try_into().unwrap_or(Ratio::zero()));
+
+        self
+    }
+
+    /// It shows `min <n>` after the sparkline.
+    pub fn set_show_min(&mut self, show_min: bool) -> &mut Self {
+        self.show_min = show_min;
+
+        self
+    }
+
+    /// It shows `max <n>` after the sparkline.
+    pub fn set_show_max(&mut self, show_max: bool) -> &mut Self {
+        self.show_max = show_max;
+
+        self
+    }
+
+    /// It shows `last <n>` after the sparkline.
+    pub fn set_show_last(&mut self, show_last: bool) -> &mut Self {
+        self.show_last = show_last;
+
+        self
+    }
+
+    /// It's used to format the annotations.
+    pub fn set_formatter(&mut self, formatter: Box<dyn NumberFormatter>) -> &mut Self {
+        self.formatter = formatter.into();
+
+        self
+    }
+
+    /// It's default to `Downsampling::Mean`.
+    pub fn set_downsampling(&mut self, downsampling: Downsampling) -> &mut Self {
+        self.downsampling = downsampling;
+
+        self
+    }
+
+    pub fn set_color_mode(&mut self, color_mode: ColorMode) -> &mut Self {
+        self.color_mode = color_mode;
+
+        self
+    }
+
+    pub fn set_primary_color(&mut self, color: Option<Color>) -> &mut Self {
+        self.primary_color = color;
+
+        self
+    }
+
+    pub fn set_annotation_color(&mut self, color: Option<Color>) -> &mut Self {
+        self.annotation_color = color;
+
+        self
+    }
+
+    pub fn set_charset(&mut self, charset: Charset) -> &mut Self {
+        self.charset = charset;
+
+        self
+    }
+
+    /// It panics if the data is empty.
+    pub fn draw(&self) -> String {
+        self.draw_lines().to_string(&self.color_mode)
+    }
+
+    pub(crate) fn draw_lines(&self) -> Lines {
+        assert!(!self.data.is_empty(), "Cannot draw a sparkline without any data");
+
+        let width = self.width.unwrap_or(self.data.len());
+        let labeled_data = self.data.iter().map(|n| (String::new(), n.clone())).collect::<Vec<_>>();
+        let data = match self.downsampling.downsample(&labeled_data, width) {
+            Some(data) => data.into_iter().map(|(_, n)| n).collect(),
+            None => self.data.clone(),
+        };
+
+        let (data_min, data_max) = min_max(&data);
+        let y_min = self.y_min.clone().unwrap_or(data_min.clone());
+        let y_max = self.y_max.clone().unwrap_or(data_max.clone());
+        let y_diff = y_max.sub_rat(&y_min);
+        let glyphs = self.charset.glyphs();
+        let mut result = Lines::new(width, 1);
+
+        for x in 0..width {
+            let n = &data[x * data.len() / width];
+
+            // truncate((n - y_min) / y_diff * 8), clamped to 0..=7
+            let level = if y_diff.is_zero() || n.leq_rat(&y_min) {
+                0
+            } else if n.geq_rat(&y_max) {
+                7
+            } else {
+                n.sub_rat(&y_min).div_rat(&y_diff).mul_i32(8).truncate_bi().to_i32().unwrap_or(0).clamp(0, 7) as usize
+            };
+
+            result.set(x, 0, glyphs.sparkline[level]);
+        }
+
+        result.set_color_all(self.primary_color.clone());
+
+        // the annotations show the original data, not the downsampled one
+        let (data_min, data_max) = min_max(&self.data);
+        let mut annotations = vec![];
+
+        if self.show_min {
+            annotations.push(format!("min {}", self.formatter.f(data_min)));
+        }
+
+        if self.show_max {
+            annotations.push(format!("max {}", self.formatter.f(data_max)));
+        }
+
+        if self.show_last {
+            annotations.push(format!("last {}", self.formatter.f(&self.data[self.data.len() - 1])));
+        }
+
+        if !annotations.is_empty() {
+            let mut annotations = Lines::from_string(&format!(" {}", annotations.join(" ")), Alignment::First, &ColorMode::None);
+            annotations.set_color_all(self.annotation_color.clone());
+            result = result.merge_horizontally(&annotations, Alignment::First);
+        }
+
+        result
+    }
+}
+
+// `data` is never empty
+fn min_max(data: &[Ratio]) -> (&Ratio, &Ratio) {
+    let mut min = &data[0];
+    let mut max = &data[0];
+
+    for n in data.iter() {
+        if n.lt_rat(min) {
+            min = n;
+        }
+
+        if n.gt_rat(max) {
+            max = n;
+        }
+    }
+
+    (min, max)
+}
+
+impl Default for Sparkline {
+    fn default() -> Self {
+        Sparkline {
+            data: vec![],
+            width: None,
+            y_min: None,
+            y_max: None,
+            show_min: false,
+            show_max: false,
+            show_last: false,
+            formatter: Arc::new(DefaultFormatter),
+            downsampling: Downsampling::Mean,
+            color_mode: ColorMode::None,
+            primary_color: None,
+            annotation_color: None,
+            charset: Charset::Unicode,
+        }
+    }
+}
+
+use std::fmt;
+
+impl fmt::Display for Sparkline {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.draw())
+    }
+}
@@ -0,0 +1,372 @@
+// Unix timestamps (in seconds, UTC) on the x axis.
+//
+// The points are resampled into the columns of the plot. Each column covers the same length of time,
+// so irregularly spaced points are placed where they belong. A column without any point holds the value of the previous column.
+// Then the engine picks the ticks of the x axis at round time boundaries (minute, hour, day, month...).
+
+use crate::numeric::Number;
+use hmath::Ratio;
+
+const MINUTE: i64 = 60;
+const HOUR: i64 = 3600;
+const DAY: i64 = 86400;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TickUnit {
+    Second(i64),
+    Minute(i64),
+    Hour(i64),
+    Day(i64),
+    Month(i64),
+    Year(i64),
+}
+
+impl TickUnit {
+    // rough length, only used to pick the unit
+    fn approx_seconds(&self) -> i64 {
+        match self {
+            TickUnit::Second(n) => *n,
+            TickUnit::Minute(n) => *n * MINUTE,
+            TickUnit::Hour(n) => *n * HOUR,
+            TickUnit::Day(n) => *n * DAY,
+            TickUnit::Month(n) => *n * DAY * 30,
+            TickUnit::Year(n) => *n * DAY * 365,
+        }
+    }
+
+    // width of the widest label of this unit
+    fn label_width(&self) -> usize {
+        match self {
+            TickUnit::Second(_) => 8,  // HH:MM:SS
+            TickUnit::Minute(_)
+            | TickUnit::Hour(_)
+            | TickUnit::Day(_) => 5,  // HH:MM, MM-DD
+            TickUnit::Month(_) => 7,  // YYYY-MM
+            TickUnit::Year(_) => 4,  // YYYY
+        }
+    }
+
+    // The first round boundary that's greater than or equal to `t`.
+    // It's `None` if the boundary doesn't fit in i64, so that extreme timestamps don't overflow.
+    fn first_tick(&self, t: i64) -> Option<i64> {
+        match self {
+            TickUnit::Month(n) => {
+                let (mut year, mut month, _) = civil_from_days(t.div_euclid(DAY));
+                month = (month - 1) / n * n + 1;
+
+                loop {
+                    let tick = days_from_civil(year, month, 1).checked_mul(DAY)?;
+
+                    if tick >= t {
+                        return Some(tick);
+                    }
+
+                    (year, month) = add_months(year, month, *n);
+                }
+            },
+            TickUnit::Year(n) => {
+                let (year, _, _) = civil_from_days(t.div_euclid(DAY));
+                let mut year = year.div_euclid(*n) * n;
+
+                loop {
+                    let tick = days_from_civil(year, 1, 1).checked_mul(DAY)?;
+
+                    if tick >= t {
+                        return Some(tick);
+                    }
+
+                    year += n;
+                }
+            },
+            _ => {
+                let step = self.approx_seconds();
+
+                // `t` is rounded up to a multiple of `step`
+                let tick = t.div_euclid(step) * step;
+
+                if tick < t {
+                    tick.checked_add(step)
+                }
+
+                else {
+                    Some(tick)
+                }
+            },
+        }
+    }
+
+    // It's `None` if the next tick doesn't fit in i64.
+    fn next_tick(&self, tick: i64) -> Option<i64> {
+        match self {
+            TickUnit::Month(n) => {
+                let (year, month, _) = civil_from_days(tick.div_euclid(DAY));
+                let (year, month) = add_months(year, month, *n);
+
+                days_from_civil(year, month, 1).checked_mul(DAY)
+            },
+            TickUnit::Year(n) => {
+                let (year, _, _) = civil_from_days(tick.div_euclid(DAY));
+
+                days_from_civil(year + n, 1, 1).checked_mul(DAY)
+            },
+            _ => tick.checked_add(self.approx_seconds()),
+        }
+    }
+
+    // The labels are formatted adaptively: when a tick of a smaller unit is on a boundary of a bigger unit
+    // (e.g. midnight on an hourly axis), it shows the bigger unit instead.
+    fn format(&self, t: i64) -> String {
+        let (year, month, day) = civil_from_days(t.div_euclid(DAY));
+        let seconds = t.rem_euclid(DAY);
+        let (hour, minute, second) = (seconds / HOUR, seconds % HOUR / MINUTE, seconds % MINUTE);
+
+        match self {
+            TickUnit::Second(_) => format!("{hour:02}:{minute:02}:{second:02}"),
+            TickUnit::Minute(_)
+            | TickUnit::Hour(_) => if seconds == 0 {
+                format!("{month:02}-{day:02}")
+            } else {
+                format!("{hour:02}:{minute:02}")
+            },
+            TickUnit::Day(_) => if month == 1 && day == 1 {
+                format!("{year:04}")
+            } else {
+                format!("{month:02}-{day:02}")
+            },
+            TickUnit::Month(_) => if month == 1 {
+                format!("{year:04}")
+            } else {
+                format!("{year:04}-{month:02}")
+            },
+            TickUnit::Year(_) => format!("{year:04}"),
+        }
+    }
+}
+
+const TICK_UNITS: [TickUnit; 26] = [
+    TickUnit::Second(1),
+    TickUnit::Second(5),
+    TickUnit::Second(15),
+    TickUnit::Second(30),
+    TickUnit::Minute(1),
+    TickUnit::Minute(5),
+    TickUnit::Minute(15),
+    TickUnit::Minute(30),
+    TickUnit::Hour(1),
+    TickUnit::Hour(3),
+    TickUnit::Hour(6),
+    TickUnit::Hour(12),
+    TickUnit::Day(1),
+    TickUnit::Day(2),
+    TickUnit::Day(7),
+    TickUnit::Day(14),
+    TickUnit::Month(1),
+    TickUnit::Month(3),
+    TickUnit::Month(6),
+    TickUnit::Year(1),
+    TickUnit::Year(2),
+    TickUnit::Year(5),
+    TickUnit::Year(10),
+    TickUnit::Year(20),
+    TickUnit::Year(50),
+    TickUnit::Year(100),
+];
+
+// The column of the plot where `t` is drawn by `resample_time_series`. It's `None` if `t` is out of `time_range`.
+pub fn get_column(t: i64, time_range: (i64, i64), width: usize) -> Option<usize> {
+    let (t_min, t_max) = (time_range.0, time_range.1.max(time_range.0));
+
+    if t < t_min || t > t_max {
+        return None;
+    }
+
+    // `+ 1` so that `t_max` is in the last column. It's widened before the subtraction, which overflows i64 with extreme timestamps.
+    let span = t_max as i128 - t_min as i128 + 1;
+
+    Some(((t as i128 - t_min as i128) * width as i128 / span) as usize)
+}
+
+/// It returns the resampled data (one point per column) and the ticks of the x axis (column, label).
+/// `data` must not be empty and it must be sorted by the timestamps. The x axis goes from `time_range.0` to `time_range.1`,
+/// and the points out of the range are ignored.
+#[allow(clippy::type_complexity)]
+pub fn resample_time_series<N: Number>(
+    data: &[(i64, N)],
+    time_range: (i64, i64),
+    width: usize,
+    x_label_margin: usize,
+) -> (Vec<(String, Ratio)>, Vec<(usize, String)>) {
+    let (t_min, t_max) = (time_range.0, time_range.1.max(time_range.0));
+
+    // `+ 1` so that `t_max` is in the last column
+    let span = t_max as i128 - t_min as i128 + 1;
+
+    // it's only called with `t` in the range
+    let column_of = |t: i64| get_column(t, time_range, width).unwrap();
+    let column_start = |column: usize| (t_min as i128 + (column as i128 * span).div_euclid(width as i128)) as i64;
+
+    let mut sums = vec![(N::zero(), 0); width];
+
+    for (t, n) in data.iter() {
+        if *t < t_min || *t > t_max {
+            continue;
+        }
+
+        let (sum, count) = &mut sums[column_of(*t)];
+        sum.add_mut(n);
+        *count += 1;
+    }
+
+    let mut result = Vec::with_capacity(width);
+
+    // the columns before the first point hold the value of the first point
+    let mut prev = sums.iter().find(|(_, count)| *count > 0).map(|(sum, count)| sum.div_usize(*count).to_ratio()).unwrap_or(Ratio::zero());
+
+    for (column, (sum, count)) in sums.into_iter().enumerate() {
+        if count > 0 {
+            prev = sum.div_usize(count).to_ratio();
+        }
+
+        result.push((format_full(column_start(column)), prev.clone()));
+    }
+
+    // the smallest unit whose labels fit in a line
+    let unit = TICK_UNITS.iter().find(
+        |unit| span / (unit.approx_seconds() as i128) < (width / (unit.label_width() + x_label_margin)).max(2) as i128
+    ).copied().unwrap_or(TickUnit::Year(100));
+
+    let mut ticks = vec![];
+    let mut tick = unit.first_tick(t_min);
+
+    while let Some(t) = tick {
+        if t > t_max {
+            break;
+        }
+
+        ticks.push((column_of(t), unit.format(t)));
+        tick = unit.next_tick(t);
+    }
+
+    (result, ticks)
+}
+
+fn format_full(t: i64) -> String {
+    let (year, month, day) = civil_from_days(t.div_euclid(DAY));
+    let seconds = t.rem_euclid(DAY);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        seconds / HOUR,
+        seconds % HOUR / MINUTE,
+        seconds % MINUTE,
+    )
+}
+
+fn add_months(year: i64, month: i64, n: i64) -> (i64, i64) {
+    let months = year * 12 + month - 1 + n;
+
+    (months.div_euclid(12), months.rem_euclid(12) + 1)
+}
+
+// <http://howardhinnant.github.io/date_algorithms.html>
+fn civil_from_days(days: i64) -> (i64, i64, i64) {  // (year, month, day)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146097 + doe - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-01 00:00:00
+    const NEW_YEAR_2024: i64 = 1_704_067_200;
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(NEW_YEAR_2024 / DAY - 1), (2023, 12, 31));
+
+        // leap years
+        assert_eq!(civil_from_days(days_from_civil(2000, 2, 28) + 1), (2000, 2, 29));
+        assert_eq!(civil_from_days(days_from_civil(1900, 2, 28) + 1), (1900, 3, 1));
+        assert_eq!(civil_from_days(days_from_civil(2024, 2, 28) + 1), (2024, 2, 29));
+        assert_eq!(civil_from_days(days_from_civil(2023, 2, 28) + 1), (2023, 3, 1));
+
+        for days in (-800_000..800_000).step_by(997) {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn month_rollover() {
+        assert_eq!(add_months(2023, 12, 1), (2024, 1));
+        assert_eq!(add_months(2024, 1, -1), (2023, 12));
+        assert_eq!(add_months(2023, 11, 3), (2024, 2));
+        assert_eq!(add_months(-1, 12, 1), (0, 1));
+        assert_eq!(add_months(0, 1, -1), (-1, 12));
+    }
+
+    #[test]
+    fn adaptive_format() {
+        assert_eq!(TickUnit::Hour(1).format(NEW_YEAR_2024 + 3 * HOUR), "03:00");
+        assert_eq!(TickUnit::Hour(1).format(NEW_YEAR_2024 + DAY), "01-02");
+        assert_eq!(TickUnit::Day(1).format(NEW_YEAR_2024 - DAY), "12-31");
+        assert_eq!(TickUnit::Day(1).format(NEW_YEAR_2024), "2024");
+        assert_eq!(TickUnit::Month(1).format(NEW_YEAR_2024 - 31 * DAY), "2023-12");
+        assert_eq!(TickUnit::Month(1).format(NEW_YEAR_2024), "2024");
+        assert_eq!(TickUnit::Second(1).format(-1), "23:59:59");
+        assert_eq!(format_full(-1), "1969-12-31 23:59:59");
+    }
+
+    #[test]
+    fn month_ticks_over_new_year() {
+        let unit = TickUnit::Month(1);
+        let tick = unit.first_tick(NEW_YEAR_2024 - 40 * DAY).unwrap();
+
+        assert_eq!(unit.format(tick), "2023-12");
+        assert_eq!(unit.next_tick(tick), Some(NEW_YEAR_2024));
+    }
+
+    #[test]
+    fn weekly_ticks() {
+        let data = (0..30).map(|day| (NEW_YEAR_2024 + day * DAY, Ratio::from_i32(day as i32))).collect::<Vec<_>>();
+        let (_, ticks) = resample_time_series(&data, (data[0].0, data[29].0), 60, 2);
+
+        assert!(ticks.len() >= 3, "{ticks:?}");
+    }
+
+    #[test]
+    fn extreme_timestamps() {
+        assert_eq!(get_column(i64::MIN, (i64::MIN, i64::MAX), 10), Some(0));
+        assert_eq!(get_column(i64::MAX, (i64::MIN, i64::MAX), 10), Some(9));
+
+        let data = [(i64::MIN, Ratio::zero()), (0, Ratio::one()), (i64::MAX, Ratio::from_i32(2))];
+        let (resampled, _) = resample_time_series(&data, (i64::MIN, i64::MAX), 10, 2);
+        assert_eq!(resampled.len(), 10);
+
+        let data = [(i64::MAX - 10, Ratio::zero()), (i64::MAX, Ratio::one())];
+        let (resampled, _) = resample_time_series(&data, (i64::MAX - 10, i64::MAX), 4, 2);
+        assert_eq!(resampled.len(), 4);
+    }
+}
@@ -1,3 +1,30 @@
-pub fn into_v16(s: &str) -> Vec<u16> {
-    String::from(s).encode_utf16().filter(|c| *c != 13).collect()
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// The number of terminal cells that `s` occupies. A grapheme occupies 1 or 2 cells.
+pub fn display_width(s: &str) -> usize {
+    s.graphemes(true).map(grapheme_width).sum()
+}
+
+pub fn grapheme_width(g: &str) -> usize {
+    // zero-width graphemes (e.g. a combining mark at the beginning of a string) still take a cell
+    g.width().clamp(1, 2)
+}
+
+/// It truncates `s` so that the result is not wider than `width`.
+pub fn truncate_to_width(s: &str, width: usize) -> String {
+    let mut result = String::new();
+    let mut curr_width = 0;
+
+    for g in s.graphemes(true) {
+        curr_width += grapheme_width(g);
+
+        if curr_width > width {
+            break;
+        }
+
+        result.push_str(g);
+    }
+
+    result
 }
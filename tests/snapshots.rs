@@ -0,0 +1,239 @@
+// Each test draws a graph and compares it with `tests/snapshots/{name}.txt`.
+// The snapshots were drawn before `Lines` was stored in a flat buffer, so the output of the engine must stay the same.
+// If you change the output on purpose, run `UPDATE_SNAPSHOTS=1 cargo test --test snapshots` and review the diff.
+
+use asciigraph::*;
+use std::fs;
+use std::path::PathBuf;
+
+fn check_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("snapshots").join(format!("{name}.txt"));
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| panic!("{} doesn't exist", path.display()));
+    assert_eq!(expected, actual, "{name} doesn't match its snapshot");
+}
+
+// values from 0 to 16, and then from 1000 to 1010, so that the engine skips the range between them
+fn data_with_gap() -> Vec<i32> {
+    (0..40).map(|n| n % 7 + n / 4).chain((0..40).map(|n| 1000 + (n * 3) % 11)).collect()
+}
+
+#[test]
+fn data_1d() {
+    let mut graph = Graph::new(48, 12);
+    graph.set_1d_data(&(0..100).map(|n| (n * n) % 37).collect::<Vec<_>>())
+        .set_title("1D data");
+
+    check_snapshot("data_1d", &graph.draw());
+}
+
+// the values that are cached by the first drawing must be reset by the setters
+#[test]
+fn data_1d_redrawn() {
+    let mut graph = Graph::new(20, 12);
+    graph.set_1d_data(&(0..100).map(|n| n % 5).collect::<Vec<_>>())
+        .set_title("1D data");
+    graph.draw();
+
+    graph.set_1d_data(&(0..100).map(|n| (n * n) % 37).collect::<Vec<_>>())
+        .set_plot_width(48);
+
+    check_snapshot("data_1d", &graph.draw());
+}
+
+#[test]
+fn data_1d_block_width() {
+    let mut graph = Graph::default();
+    graph.set_1d_data(&[0, 1, 1, 0, 2, 0, 1, 2, 0, 0, 0, 1, 0, 5])
+        .set_plot_height(8)
+        .set_block_width(3)
+        .set_y_range(-1, 6);
+
+    check_snapshot("data_1d_block_width", &graph.draw());
+}
+
+#[test]
+fn automatic_skip() {
+    let mut graph = Graph::new(40, 20);
+    graph.set_1d_data(&data_with_gap());
+
+    check_snapshot("automatic_skip", &graph.draw());
+}
+
+#[test]
+fn manual_skip() {
+    let mut graph = Graph::new(40, 20);
+    graph.set_1d_data(&data_with_gap())
+        .set_skip_range(SkipValue::manual(60, 990));
+
+    check_snapshot("manual_skip", &graph.draw());
+}
+
+#[test]
+fn multiple_skip_ranges() {
+    let mut graph = Graph::new(40, 24);
+    graph.set_1d_data(&[1, 3, 2, 4, 502, 504, 501, 503, 1001, 1004, 1002, 1003, 2, 3])
+        .set_skip_range(SkipValue::manual_ranges(&[(6, 498), (507, 997)]));
+
+    check_snapshot("multiple_skip_ranges", &graph.draw());
+}
+
+#[test]
+fn horizontal_breaks() {
+    let mut graph = Graph::new(40, 10);
+    graph.set_1d_data(&(0..80).map(|n| (n * 5) % 23).collect::<Vec<_>>())
+        .add_horizontal_break(10, 19)
+        .add_horizontal_break(50, 59);
+
+    check_snapshot("horizontal_breaks", &graph.draw());
+}
+
+#[test]
+fn labeled_intervals() {
+    let mut graph = Graph::new(40, 10);
+    graph.set_1d_data(&(0..40).map(|n| (n * 3) % 17).collect::<Vec<_>>())
+        .add_labeled_interval(0, 9, "first")
+        .add_labeled_interval(20, 35, "second");
+
+    check_snapshot("labeled_intervals", &graph.draw());
+}
+
+#[test]
+fn data_2d() {
+    let mut graph = Graph::new(40, 20);
+    graph.set_2d_data_high_resolution(
+        &(0..80usize).flat_map(
+            |x| (0..40usize).map(move |y| (x, y))
+        ).filter(
+            |(x, y)| {
+                let d = x.abs_diff(40) * x.abs_diff(40) + y.abs_diff(20) * y.abs_diff(20) * 4;
+
+                (900..1200).contains(&d)
+            }
+        ).collect::<Vec<_>>(),
+        &(0..40).map(|x| if x % 10 == 0 { Some(x.to_string()) } else { None }).collect::<Vec<_>>(),
+        &(0..20).map(|y| if y % 5 == 0 { Some(y.to_string()) } else { None }).collect::<Vec<_>>(),
+    );
+
+    check_snapshot("data_2d", &graph.draw());
+}
+
+#[test]
+fn heatmap() {
+    let mut graph = Graph::new(16, 8);
+    graph.set_heatmap_data(
+        &(0..8).map(|y| (0..16).map(|x| x * y).collect::<Vec<_>>()).collect::<Vec<_>>(),
+        &(0..16).map(|x| if x % 4 == 0 { Some(format!("x{x}")) } else { None }).collect::<Vec<_>>(),
+        &(0..8).map(|y| if y % 2 == 0 { Some(format!("y{y}")) } else { None }).collect::<Vec<_>>(),
+    );
+
+    check_snapshot("heatmap", &graph.draw());
+}
+
+fn box_plot_data() -> Vec<(String, Vec<i32>)> {
+    vec![
+        (String::from("a"), vec![1, 2, 3, 4, 5, 6, 7, 8, 30]),
+        (String::from("b"), vec![10, 12, 13, 15, 18, 20]),
+        (String::from("c"), vec![]),
+        (String::from("d"), vec![5, 5, 6, 7, 7, 8, 9, 25, -10]),
+    ]
+}
+
+#[test]
+fn box_plot_vertical() {
+    let mut graph = Graph::new(40, 16);
+    graph.set_box_plot_data(&box_plot_data());
+
+    check_snapshot("box_plot_vertical", &graph.draw());
+}
+
+// The label of the maximum at the right end is newer than the snapshot of the flat buffer, and it's the only difference.
+#[test]
+fn box_plot_horizontal() {
+    let mut graph = Graph::new(40, 12);
+    graph.set_box_plot_data(&box_plot_data())
+        .set_orientation(Orientation::Horizontal);
+
+    check_snapshot("box_plot_horizontal", &graph.draw());
+}
+
+#[test]
+fn time_series() {
+    // every 10 minutes, with a missing hour
+    let data = (0..48).filter(
+        |n| !(20..26).contains(n)
+    ).map(
+        |n| (1_700_000_000 + n * 600, (n * 7) % 13)
+    ).collect::<Vec<(i64, i64)>>();
+    let mut graph = Graph::new(48, 10);
+    graph.set_time_series_data(&data);
+
+    check_snapshot("time_series", &graph.draw());
+}
+
+#[test]
+fn wide_labels() {
+    let mut graph = Graph::new(24, 6);
+    graph.set_1d_labeled_data(&[
+        (String::from("월요일"), 3),
+        (String::from("火曜日"), 5),
+        (String::from("水"), 2),
+        (String::from("木曜日です"), 7),
+        (String::from("金"), 1),
+        (String::from("土曜"), 4),
+    ]).set_title("한글 제목");
+
+    check_snapshot("wide_labels", &graph.draw());
+}
+
+#[test]
+fn paddings_and_axis_labels() {
+    let mut graph = Graph::new(30, 8);
+    graph.set_1d_data(&(0..30).map(|n| (n * 11) % 19).collect::<Vec<_>>())
+        .set_paddings([1, 2, 3, 4])
+        .set_x_axis_label("x axis")
+        .set_y_axis_label("y axis\nsecond line")
+        .set_y_label_margin(1)
+        .set_x_label_margin(2);
+
+    check_snapshot("paddings_and_axis_labels", &graph.draw());
+}
+
+#[test]
+fn big_title() {
+    let mut graph = Graph::new(40, 6);
+    graph.set_1d_data(&(0..40).map(|n| n % 9).collect::<Vec<_>>())
+        .set_title("Hello World!")
+        .set_big_title(true);
+
+    check_snapshot("big_title", &graph.draw());
+}
+
+#[test]
+fn terminal_colors() {
+    let mut graph = Graph::new(30, 20);
+    graph.set_1d_data(&data_with_gap())
+        .add_labeled_interval(5, 30, "colored")
+        .set_title("colors")
+        .set_title_color(Some(Color::Red))
+        .set_primary_color(Some(Color::Blue))
+        .set_color_mode(ColorMode::TerminalFg);
+
+    check_snapshot("terminal_colors", &graph.draw());
+}
+
+#[test]
+fn ascii_charset() {
+    let mut graph = Graph::new(30, 8);
+    graph.set_1d_data(&data_with_gap())
+        .add_labeled_interval(5, 30, "ascii")
+        .set_charset(Charset::Ascii);
+
+    check_snapshot("ascii_charset", &graph.draw());
+}